@@ -1,5 +1,19 @@
-use clap::Parser;
-use std::fs;
+use clap::{Parser, ValueEnum};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fmt::{self, Display},
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    num::IntErrorKind,
+    process,
+};
+use thiserror::Error;
+
+mod rebalance;
+mod stats;
+use rebalance::{plan, Planner};
+use stats::load_stats;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -7,80 +21,629 @@ use std::fs;
     version = "v1.0.0",
     about = "Advent of Code (Day 1)"
 )]
-
 struct Cli {
     #[clap(short, long)]
     input: String,
+    /// number of leading elves to report
+    #[clap(short, long, default_value_t = 3)]
+    top: usize,
+    /// read the input line by line instead of loading it all into memory
+    #[clap(short, long)]
+    stream: bool,
+    /// reject non-numeric entries instead of counting them as zero
+    #[clap(long)]
+    strict: bool,
+    /// integer type the calorie sums have to fit in
+    #[clap(short, long, value_enum, default_value_t = Width::I64)]
+    width: Width,
+    /// report the distribution of loads instead of the top elves
+    #[clap(long, conflicts_with = "stream")]
+    stats: bool,
+    /// number of histogram bins in the stats report
+    #[clap(long, default_value_t = 10)]
+    bins: usize,
+    /// suggest snack moves that even out the loads
+    #[clap(long, value_enum, conflicts_with_all = ["stream", "stats"])]
+    rebalance: Option<Planner>,
 }
 fn main() {
     let cli_args = Cli::parse();
+    let (top, width) = (cli_args.top, cli_args.width);
+
+    if cli_args.stats {
+        let input = &fs::read_to_string(cli_args.input).unwrap();
+        let loads = if cli_args.strict {
+            strict_elf_loads(input, width)
+        } else {
+            elf_loads(input, width)
+        };
+        match loads {
+            Ok(loads) => print!("{}", load_stats(&loads, cli_args.bins)),
+            Err(e) => report_error(e),
+        }
+        return;
+    }
+
+    if let Some(planner) = cli_args.rebalance {
+        let input = &fs::read_to_string(cli_args.input).unwrap();
+        // strict mode only needs to vet the input, the items are the same
+        let res = if cli_args.strict {
+            strict_elf_loads(input, width)
+                .and_then(|_| plan(&elf_items(input, width)?, planner, width))
+        } else {
+            elf_items(input, width).and_then(|items| plan(&items, planner, width))
+        };
+        match res {
+            Ok(plan) => print!("{plan}"),
+            Err(e) => report_error(e),
+        }
+        return;
+    }
+
+    let answers = if cli_args.stream {
+        let reader = BufReader::new(File::open(cli_args.input).unwrap());
+        stream_loads(reader, top, cli_args.strict, width).map(|a| vec![a])
+    } else {
+        let input = &fs::read_to_string(cli_args.input).unwrap();
+        if cli_args.strict {
+            strict_loads(input, top, width).map(|a| vec![a])
+        } else {
+            school_girl(input, top, width).and_then(|a| Ok(vec![a, chest_hair(input, top, width)?]))
+        }
+    };
+
+    match answers {
+        Ok(answers) => answers.iter().for_each(|a| print_answer(a, width)),
+        Err(e) => report_error(e),
+    }
+}
+
+fn report_error(e: Error) -> ! {
+    if let Error::InvalidEntries(bad) = &e {
+        for b in bad {
+            eprintln!("{b}");
+        }
+    }
+    eprintln!("error: {e}");
+    process::exit(1);
+}
+
+#[derive(Error, Debug)]
+enum Error {
+    #[error("found {} invalid calorie entries", .0.len())]
+    InvalidEntries(Vec<BadEntry>),
+    #[error("calorie total for elf {group} does not fit in {width}")]
+    Overflow { group: usize, width: Width },
+    #[error("{elves} elves and {items} items is too many for an exact plan (elves^items can be at most {limit})")]
+    TooManyItems {
+        elves: usize,
+        items: usize,
+        limit: u64,
+    },
+    #[error("failed to read input: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// integer types we can be asked to sum calories in, everything is added
+/// up as an i128 internally and checked against the chosen type as we go
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Width {
+    I32,
+    I64,
+    I128,
+}
+
+impl Width {
+    /// adds `n` to `sum`, or None if the result doesn't fit
+    fn checked_add(self, sum: i128, n: i128) -> Option<i128> {
+        let sum = sum.checked_add(n)?;
+        let fits = match self {
+            Width::I32 => i32::try_from(sum).is_ok(),
+            Width::I64 => i64::try_from(sum).is_ok(),
+            Width::I128 => true,
+        };
+        fits.then_some(sum)
+    }
+}
+
+impl Display for Width {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Width::I32 => write!(f, "i32"),
+            Width::I64 => write!(f, "i64"),
+            Width::I128 => write!(f, "i128"),
+        }
+    }
+}
 
-    let input = &fs::read_to_string(cli_args.input).unwrap();
-    print_answer(school_girl(input));
-    print_answer(chest_hair(input));
+/// a calorie entry rejected by strict parsing, line and column are 1-based
+#[derive(Debug, PartialEq)]
+struct BadEntry {
+    line: usize,
+    column: usize,
+    group: usize,
+    entry: String,
+}
+
+impl Display for BadEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {} (elf {}): invalid calorie entry {:?}",
+            self.line, self.column, self.group, self.entry
+        )
+    }
+}
+
+/// why a calorie entry couldn't be read
+#[derive(Debug, PartialEq)]
+enum EntryError {
+    /// the (1-based) column of the first thing that isn't a digit
+    Invalid(usize),
+    /// too many digits to even fit in an i128
+    TooBig,
+}
+
+/// parses a single calorie entry, in strict mode anything other than
+/// digits is rejected, otherwise typos quietly count as 0
+///
+/// an entry too big for an i128 is never a typo, so it's reported in
+/// either mode rather than being summed as 0
+fn parse_entry(entry: &str, strict: bool) -> Result<i128, EntryError> {
+    if strict {
+        if let Some(col) = entry.chars().position(|c| !c.is_ascii_digit()) {
+            return Err(EntryError::Invalid(col + 1));
+        }
+    }
+    match entry.parse() {
+        Ok(n) => Ok(n),
+        Err(e)
+            if matches!(
+                e.kind(),
+                IntErrorKind::PosOverflow | IntErrorKind::NegOverflow
+            ) =>
+        {
+            Err(EntryError::TooBig)
+        }
+        // blank lines are just padding, not typos
+        Err(_) => Ok(0),
+    }
 }
 
 /// here's a solution for schoolgirls
-fn school_girl(input: &str) -> Vec<(usize, i32)> {
+fn school_girl(input: &str, top: usize, width: Width) -> Result<Vec<(usize, i128)>, Error> {
     let num_groups = input.split("\n\n");
-    let mut pos = 0;
     let mut ans = Vec::new();
-    for group in num_groups {
-        let sum = sum_str_list(group);
+    for (pos, group) in num_groups.enumerate() {
+        let sum = sum_str_list(group, width).ok_or(Error::Overflow { group: pos, width })?;
         ans.push((pos, sum));
-        pos += 1;
     }
-    // okay I just re-used the sort solution from the
+    // okay I just re-used the selection from the
     // better answer here to solve part 2
-    ans.sort_by(|a, b| (b.1).cmp(&a.1));
-    ans
+    Ok(top_n(ans, top))
 }
 
-fn sum_str_list(s: &str) -> i32 {
+/// returns None if the sum doesn't fit in `width`
+fn sum_str_list(s: &str, width: Width) -> Option<i128> {
     let nums = s.split('\n');
-    let mut sum: i32 = 0;
+    let mut sum: i128 = 0;
     for n in nums {
-        // since we're summing, or_default works here (giving 0 on empty)
-        // if that causes issues in the future then will need better handling
-        let n: i32 = n.parse().unwrap_or_default();
-        sum += n;
+        let n = parse_entry(n, false).ok()?;
+        sum = width.checked_add(sum, n)?;
     }
-    sum
+    Some(sum)
 }
 
 /// and here's a solution with some chest hair
-fn chest_hair(input: &str) -> Vec<(usize, i32)> {
+fn chest_hair(input: &str, top: usize, width: Width) -> Result<Vec<(usize, i128)>, Error> {
+    let ans = elf_loads(input, width)?;
+    // if we only wanted to find the elf with the heaviest load we can
+    // use the following reduce approach, but that won't work for part two
+    // .reduce(|a, i| if a.1 > i.1 { a } else { i }).unwrap()
+
+    // instead we'll keep the heaviest loads as we go
+    Ok(top_n(ans, top))
+}
+
+/// every elf's (position, total) in input order
+fn elf_loads(input: &str, width: Width) -> Result<Vec<(usize, i128)>, Error> {
     let num_groups = input.split("\n\n");
-    let mut ans = num_groups
+    let ans = num_groups
         // split each elf into indiv entries
         .map(|g| g.split('\n'))
         .map(|nums| {
             // convert str -> int
-            nums.map(|n| n.parse::<i32>().unwrap_or_default())
-                // sum each elf's totals
-                .sum::<i32>()
+            nums.map(|n| parse_entry(n, false).ok())
+                // sum each elf's totals, bailing out if they don't fit
+                .try_fold(0, |sum, n| width.checked_add(sum, n?))
         })
         // need an enumerator to track the index
         .enumerate()
-        // if we only wanted to find the elf with the heaviest load we can
-        // use the following reduce approach, but that won't work for part two
-        // .reduce(|a, i| if a.1 > i.1 { a } else { i }).unwrap()
-        .collect::<Vec<(usize, i32)>>();
-
-    // instead we'll collect all of our answers and then sort them by their values
-    ans.sort_by(|a, b| (b.1).cmp(&a.1));
-    ans
-}
-
-fn print_answer(ans: Vec<(usize, i32)>) {
-    println!(
-        "Top 3 Elves are in positions {}, {} and {} with {}, {} and {} calories for {} total",
-        ans[0].0,
-        ans[1].0,
-        ans[2].0,
-        ans[0].1,
-        ans[1].1,
-        ans[2].1,
-        ans[0].1 + ans[1].1 + ans[2].1
-    );
+        .map(|(group, sum)| {
+            sum.map(|s| (group, s))
+                .ok_or(Error::Overflow { group, width })
+        })
+        .collect::<Result<Vec<(usize, i128)>, Error>>()?;
+    Ok(ans)
+}
+
+/// each elf's individual items, blank lines are skipped, every elf's total
+/// has to fit in `width` just like it does for the answers
+fn elf_items(input: &str, width: Width) -> Result<Vec<Vec<i128>>, Error> {
+    input
+        .split("\n\n")
+        .enumerate()
+        .map(|(group, g)| {
+            let overflow = || Error::Overflow { group, width };
+            let items = g
+                .split('\n')
+                .filter(|n| !n.is_empty())
+                .map(|n| parse_entry(n, false).map_err(|_| overflow()))
+                .collect::<Result<Vec<i128>, Error>>()?;
+            items
+                .iter()
+                .try_fold(0, |sum, &n| width.checked_add(sum, n))
+                .ok_or_else(overflow)?;
+            Ok(items)
+        })
+        .collect()
+}
+
+/// same idea as `chest_hair` but keeps track of where each entry came from
+/// so bad entries can be reported instead of quietly counted as zero
+fn strict_loads(input: &str, top: usize, width: Width) -> Result<Vec<(usize, i128)>, Error> {
+    Ok(top_n(strict_elf_loads(input, width)?, top))
+}
+
+/// strict version of `elf_loads`
+fn strict_elf_loads(input: &str, width: Width) -> Result<Vec<(usize, i128)>, Error> {
+    let mut bad = Vec::new();
+    let mut loads = Vec::new();
+    let mut line = 1;
+    for (group, g) in input.split("\n\n").enumerate() {
+        let mut sum: i128 = 0;
+        for (i, entry) in g.split('\n').enumerate() {
+            match parse_entry(entry, true) {
+                Ok(n) => {
+                    sum = width
+                        .checked_add(sum, n)
+                        .ok_or(Error::Overflow { group, width })?
+                }
+                Err(EntryError::TooBig) => return Err(Error::Overflow { group, width }),
+                Err(EntryError::Invalid(column)) => bad.push(BadEntry {
+                    line: line + i,
+                    column,
+                    group,
+                    entry: entry.to_string(),
+                }),
+            }
+        }
+        // the group's own lines plus the blank separator line
+        line += g.matches('\n').count() + 2;
+        loads.push((group, sum));
+    }
+    if bad.is_empty() {
+        Ok(loads)
+    } else {
+        Err(Error::InvalidEntries(bad))
+    }
+}
+
+/// and one for inputs too big to hold in memory, walks the lines and
+/// only keeps the running sum of the current elf plus the top `top`
+///
+/// elves are split exactly like `input.split("\n\n")` would, so a run
+/// of blank lines gives the same (zero-calorie) groups as `chest_hair`
+fn stream_loads<R: BufRead>(
+    mut reader: R,
+    top: usize,
+    strict: bool,
+    width: Width,
+) -> Result<Vec<(usize, i128)>, Error> {
+    let mut loads = TopLoads::new(top);
+    let mut bad = Vec::new();
+    let mut line = String::new();
+    let mut line_num = 0;
+    let mut pos = 0;
+    let mut sum: i128 = 0;
+    let mut first_line = true;
+    let mut prev_sep = false;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_num += 1;
+        // a blank line only splits groups if the newline before it wasn't
+        // already eaten by the previous separator (split doesn't overlap)
+        let is_sep = line == "\n" && !first_line && !prev_sep;
+        if is_sep {
+            loads.push(pos, sum);
+            pos += 1;
+            sum = 0;
+        } else {
+            let entry = line.strip_suffix('\n').unwrap_or(&line);
+            match parse_entry(entry, strict) {
+                Ok(n) => {
+                    sum = width
+                        .checked_add(sum, n)
+                        .ok_or(Error::Overflow { group: pos, width })?
+                }
+                Err(EntryError::TooBig) => return Err(Error::Overflow { group: pos, width }),
+                Err(EntryError::Invalid(column)) => bad.push(BadEntry {
+                    line: line_num,
+                    column,
+                    group: pos,
+                    entry: entry.to_string(),
+                }),
+            }
+        }
+        prev_sep = is_sep;
+        first_line = false;
+    }
+    // there's always a last group, even if it's empty
+    loads.push(pos, sum);
+    if bad.is_empty() {
+        Ok(loads.into_vec())
+    } else {
+        Err(Error::InvalidEntries(bad))
+    }
+}
+
+/// running selection of the `n` heaviest loads using a bounded min-heap
+struct TopLoads {
+    n: usize,
+    heap: BinaryHeap<Reverse<(i128, Reverse<usize>)>>,
+}
+
+impl TopLoads {
+    fn new(n: usize) -> Self {
+        TopLoads {
+            n,
+            heap: BinaryHeap::with_capacity(n + 1),
+        }
+    }
+
+    fn push(&mut self, pos: usize, sum: i128) {
+        // the heap root is the lightest load we're holding on to, so
+        // once we're over capacity it's the one that gets kicked out
+        self.heap.push(Reverse((sum, Reverse(pos))));
+        if self.heap.len() > self.n {
+            self.heap.pop();
+        }
+    }
+
+    /// heaviest first, ties keep their original order (same as a stable sort)
+    fn into_vec(self) -> Vec<(usize, i128)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((sum, Reverse(pos)))| (pos, sum))
+            .collect()
+    }
+}
+
+/// selects the `n` heaviest loads, heaviest first
+fn top_n(loads: impl IntoIterator<Item = (usize, i128)>, n: usize) -> Vec<(usize, i128)> {
+    let mut top = TopLoads::new(n);
+    for (pos, sum) in loads {
+        top.push(pos, sum);
+    }
+    top.into_vec()
+}
+
+/// joins items as "a, b and c"
+fn join_list<T: Display>(items: impl Iterator<Item = T>) -> String {
+    let items: Vec<String> = items.map(|i| i.to_string()).collect();
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn print_answer(ans: &[(usize, i128)], width: Width) {
+    // every load fits on its own, but their total might not
+    let total = match ans.iter().try_fold(0, |t, a| width.checked_add(t, a.1)) {
+        Some(total) => total.to_string(),
+        None => format!("more than {width} can hold in"),
+    };
+    match ans.len() {
+        0 => println!("No Elves found"),
+        1 => println!(
+            "Top Elf is in position {} with {} calories",
+            ans[0].0, ans[0].1
+        ),
+        n => println!(
+            "Top {} Elves are in positions {} with {} calories for {} total",
+            n,
+            join_list(ans.iter().map(|a| a.0)),
+            join_list(ans.iter().map(|a| a.1)),
+            total
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        chest_hair, elf_items, join_list, parse_entry, school_girl, stream_loads, strict_loads,
+        top_n, BadEntry, EntryError, Error, Width,
+    };
+
+    const TEST_INPUT: &str =
+        "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n";
+
+    #[test]
+    fn test_top_n() {
+        let loads = vec![(0, 5), (1, 9), (2, 5), (3, 1), (4, 7)];
+        assert_eq!(top_n(loads.clone(), 3), vec![(1, 9), (4, 7), (0, 5)]);
+        assert_eq!(top_n(loads.clone(), 0), vec![]);
+        assert_eq!(
+            top_n(loads, 10),
+            vec![(1, 9), (4, 7), (0, 5), (2, 5), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_solutions_agree() {
+        let expected = vec![(3, 24000), (2, 11000), (4, 10000)];
+        assert_eq!(school_girl(TEST_INPUT, 3, Width::I32).unwrap(), expected);
+        assert_eq!(chest_hair(TEST_INPUT, 3, Width::I32).unwrap(), expected);
+        assert_eq!(
+            chest_hair("100\n200", 3, Width::I32).unwrap(),
+            vec![(0, 300)]
+        );
+    }
+
+    #[test]
+    fn test_stream_loads() {
+        let inputs = [
+            TEST_INPUT,
+            "",
+            "\n",
+            "\n\n",
+            "\n\n\n10",
+            "5\n\n\n10\n\n\n\n20\n",
+            "1\nx\n2\n\n3",
+        ];
+        for input in inputs {
+            assert_eq!(
+                stream_loads(input.as_bytes(), 10, false, Width::I64).unwrap(),
+                chest_hair(input, 10, Width::I64).unwrap(),
+                "input {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        assert_eq!(parse_entry("1000", true), Ok(1000));
+        assert_eq!(parse_entry("", true), Ok(0));
+        assert_eq!(parse_entry("10O0", true), Err(EntryError::Invalid(3)));
+        assert_eq!(parse_entry("-5", true), Err(EntryError::Invalid(1)));
+        assert_eq!(parse_entry("10O0", false), Ok(0));
+        let huge = "1".repeat(44);
+        assert_eq!(parse_entry(&huge, true), Err(EntryError::TooBig));
+        assert_eq!(parse_entry(&huge, false), Err(EntryError::TooBig));
+    }
+
+    #[test]
+    fn test_strict_loads() {
+        assert_eq!(
+            strict_loads(TEST_INPUT, 3, Width::I64).unwrap(),
+            chest_hair(TEST_INPUT, 3, Width::I64).unwrap()
+        );
+
+        let input = "1000\n2000\n\n10O0\n\n5000\n6000 \n";
+        let expected = vec![
+            BadEntry {
+                line: 4,
+                column: 3,
+                group: 1,
+                entry: "10O0".to_string(),
+            },
+            BadEntry {
+                line: 7,
+                column: 5,
+                group: 2,
+                entry: "6000 ".to_string(),
+            },
+        ];
+        match strict_loads(input, 3, Width::I64) {
+            Err(Error::InvalidEntries(bad)) => assert_eq!(bad, expected),
+            other => panic!("expected invalid entries, got {other:?}"),
+        }
+        match stream_loads(input.as_bytes(), 3, true, Width::I64) {
+            Err(Error::InvalidEntries(bad)) => assert_eq!(bad, expected),
+            other => panic!("expected invalid entries, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let input = "5\n\n2147483647\n1\n";
+        assert!(matches!(
+            chest_hair(input, 3, Width::I32),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+        assert!(matches!(
+            school_girl(input, 3, Width::I32),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+        assert!(matches!(
+            strict_loads(input, 3, Width::I32),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+        assert!(matches!(
+            stream_loads(input.as_bytes(), 3, false, Width::I32),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+        assert_eq!(
+            chest_hair(input, 3, Width::I64).unwrap(),
+            vec![(1, 2147483648), (0, 5)]
+        );
+
+        let huge = format!("{}\n{}", i128::MAX, 1);
+        assert!(matches!(
+            chest_hair(&huge, 3, Width::I128),
+            Err(Error::Overflow { group: 0, .. })
+        ));
+
+        // too many digits to parse at all is an overflow too, not a typo
+        let input = format!("5\n\n{}\n", "9".repeat(44));
+        assert!(matches!(
+            chest_hair(&input, 3, Width::I128),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+        assert!(matches!(
+            school_girl(&input, 3, Width::I128),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+        assert!(matches!(
+            strict_loads(&input, 3, Width::I128),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+        for strict in [false, true] {
+            assert!(matches!(
+                stream_loads(input.as_bytes(), 3, strict, Width::I128),
+                Err(Error::Overflow { group: 1, .. })
+            ));
+        }
+        assert!(matches!(
+            elf_items(&input, Width::I128),
+            Err(Error::Overflow { group: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_elf_items() {
+        assert_eq!(
+            elf_items("1\n2\n\n3\n", Width::I128).unwrap(),
+            vec![vec![1, 2], vec![3]]
+        );
+        assert_eq!(
+            elf_items("", Width::I128).unwrap(),
+            vec![Vec::<i128>::new()]
+        );
+
+        // totals are checked as well as the entries themselves
+        let input = format!("{}\n1\n\n5", i128::MAX);
+        assert!(matches!(
+            elf_items(&input, Width::I128),
+            Err(Error::Overflow { group: 0, .. })
+        ));
+        assert!(matches!(
+            elf_items("3000000000\n\n1", Width::I32),
+            Err(Error::Overflow { group: 0, .. })
+        ));
+        assert!(elf_items("3000000000\n\n1", Width::I64).is_ok());
+    }
+
+    #[test]
+    fn test_join_list() {
+        assert_eq!(join_list([1].iter()), "1");
+        assert_eq!(join_list([1, 2].iter()), "1 and 2");
+        assert_eq!(join_list([1, 2, 3].iter()), "1, 2 and 3");
+    }
 }
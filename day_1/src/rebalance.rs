@@ -0,0 +1,357 @@
+use std::fmt::{self, Display};
+
+use clap::ValueEnum;
+
+use crate::{Error, Width};
+
+/// most assignments (elves to the power of items) we're willing to brute
+/// force in exact mode
+pub const EXACT_SEARCH_LIMIT: u64 = 1 << 20;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Planner {
+    /// keep moving a snack from the heaviest elf to the lightest one
+    Greedy,
+    /// branch and bound over every assignment, small inputs only
+    Exact,
+}
+
+/// a single snack handed over from one elf to another
+#[derive(Debug, PartialEq)]
+pub struct Move {
+    pub calories: i128,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Plan {
+    pub moves: Vec<Move>,
+    /// each elf's load once the moves are done
+    pub loads: Vec<i128>,
+}
+
+impl Plan {
+    pub fn max_load(&self) -> i128 {
+        self.loads.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// works out which snacks to move so the heaviest elf carries as little as
+/// possible, `elves` holds each elf's items in input order
+///
+/// every load has to fit in `width`, plans that need a bigger one are
+/// never picked
+pub fn plan(elves: &[Vec<i128>], planner: Planner, width: Width) -> Result<Plan, Error> {
+    let items = flatten(elves);
+    let assign = match planner {
+        Planner::Greedy => greedy(&items, elves.len(), width)?,
+        Planner::Exact => {
+            let size = u32::try_from(items.len())
+                .ok()
+                .and_then(|n| (elves.len() as u64).checked_pow(n));
+            if size.is_none_or(|s| s > EXACT_SEARCH_LIMIT) {
+                return Err(Error::TooManyItems {
+                    elves: elves.len(),
+                    items: items.len(),
+                    limit: EXACT_SEARCH_LIMIT,
+                });
+            }
+            exact(&items, elves.len(), width)?
+        }
+    };
+    to_plan(&items, &assign, elves.len(), width)
+}
+
+/// (calories, owner) for every item
+fn flatten(elves: &[Vec<i128>]) -> Vec<(i128, usize)> {
+    elves
+        .iter()
+        .enumerate()
+        .flat_map(|(elf, items)| items.iter().map(move |&i| (i, elf)))
+        .collect()
+}
+
+fn loads_for(
+    items: &[(i128, usize)],
+    assign: &[usize],
+    num_elves: usize,
+    width: Width,
+) -> Result<Vec<i128>, Error> {
+    let mut loads = vec![0; num_elves];
+    for (item, &elf) in items.iter().zip(assign) {
+        loads[elf] = width
+            .checked_add(loads[elf], item.0)
+            .ok_or(Error::Overflow { group: elf, width })?;
+    }
+    Ok(loads)
+}
+
+fn to_plan(
+    items: &[(i128, usize)],
+    assign: &[usize],
+    num_elves: usize,
+    width: Width,
+) -> Result<Plan, Error> {
+    let moves = items
+        .iter()
+        .zip(assign)
+        .filter(|(item, &elf)| item.1 != elf)
+        .map(|(item, &elf)| Move {
+            calories: item.0,
+            from: item.1,
+            to: elf,
+        })
+        .collect();
+    Ok(Plan {
+        moves,
+        loads: loads_for(items, assign, num_elves, width)?,
+    })
+}
+
+/// local search, moves the snack from the heaviest elf that best evens
+/// it out with the lightest elf until no move helps any more
+///
+/// every move shrinks the sum of squared loads so this always finishes,
+/// but it can get stuck where only a swap would improve things
+///
+/// both new loads end up between the old ones, so once the starting loads
+/// fit in `width` they always do
+fn greedy(items: &[(i128, usize)], num_elves: usize, width: Width) -> Result<Vec<usize>, Error> {
+    let mut assign: Vec<usize> = items.iter().map(|i| i.1).collect();
+    let mut loads = loads_for(items, &assign, num_elves, width)?;
+    loop {
+        let heavy = (0..num_elves).max_by_key(|&e| loads[e]);
+        let light = (0..num_elves).min_by_key(|&e| loads[e]);
+        let (heavy, light) = match (heavy, light) {
+            (Some(h), Some(l)) if h != l => (h, l),
+            _ => break,
+        };
+        let gap = loads[heavy] - loads[light];
+        // only snacks smaller than the gap make the pair more even, and
+        // the closer to half the gap the better
+        let best = (0..items.len())
+            .filter(|&i| assign[i] == heavy && items[i].0 > 0 && items[i].0 < gap)
+            .min_by_key(|&i| (gap - 2 * items[i].0).abs());
+        match best {
+            Some(i) => {
+                assign[i] = light;
+                loads[heavy] =
+                    width
+                        .checked_add(loads[heavy], -items[i].0)
+                        .ok_or(Error::Overflow {
+                            group: heavy,
+                            width,
+                        })?;
+                loads[light] =
+                    width
+                        .checked_add(loads[light], items[i].0)
+                        .ok_or(Error::Overflow {
+                            group: light,
+                            width,
+                        })?;
+            }
+            None => break,
+        }
+    }
+    Ok(assign)
+}
+
+/// branch and bound over every assignment, minimising the max load, the
+/// first plan found for the best load wins so items stay put where they can
+///
+/// an elf's load going past `width` on the way cuts that branch off, big
+/// items go first so that only happens if it never comes back down
+fn exact(items: &[(i128, usize)], num_elves: usize, width: Width) -> Result<Vec<usize>, Error> {
+    if num_elves == 0 {
+        return Ok(Vec::new());
+    }
+
+    // big items first so the bound kicks in early
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(items[i].0));
+
+    // greedy gives us a decent starting point to beat
+    let start = greedy(items, num_elves, width)?;
+    // nobody can carry less than an even share, the total might not fit in
+    // an i128 though and then there's no floor to go on
+    let floor = items
+        .iter()
+        .try_fold(0i128, |sum, i| sum.checked_add(i.0))
+        .and_then(|total| total.checked_add(num_elves as i128 - 1))
+        .map_or(i128::MIN, |total| total.div_euclid(num_elves as i128));
+    let mut search = Search {
+        items,
+        order,
+        width,
+        floor,
+        best_max: loads_for(items, &start, num_elves, width)?
+            .into_iter()
+            .max()
+            .unwrap_or(0),
+        best: start,
+        assign: vec![0; items.len()],
+        loads: vec![0; num_elves],
+    };
+    search.run(0);
+    Ok(search.best)
+}
+
+struct Search<'a> {
+    items: &'a [(i128, usize)],
+    order: Vec<usize>,
+    width: Width,
+    floor: i128,
+    best: Vec<usize>,
+    best_max: i128,
+    assign: Vec<usize>,
+    loads: Vec<i128>,
+}
+
+impl Search<'_> {
+    fn run(&mut self, depth: usize) {
+        // nothing beats an even share, so stop looking once we have one
+        let cur_max = self.loads.iter().copied().max().unwrap_or(0);
+        if cur_max.max(self.floor) >= self.best_max {
+            return;
+        }
+        if depth == self.order.len() {
+            self.best_max = cur_max;
+            self.best = self.assign.clone();
+            return;
+        }
+
+        let i = self.order[depth];
+        let (calories, owner) = self.items[i];
+        // leaving the item where it is costs nothing so try that first,
+        // then the lightest elves
+        let mut elves: Vec<usize> = (0..self.loads.len()).collect();
+        elves.sort_by_key(|&e| (e != owner, self.loads[e]));
+        // elves carrying the same load are interchangeable as far as the
+        // max load goes, so only one of them needs trying
+        let mut tried: Vec<i128> = Vec::new();
+        for elf in elves {
+            if tried.contains(&self.loads[elf]) {
+                continue;
+            }
+            tried.push(self.loads[elf]);
+            let old = self.loads[elf];
+            self.loads[elf] = match self.width.checked_add(old, calories) {
+                Some(load) => load,
+                None => continue,
+            };
+            self.assign[i] = elf;
+            self.run(depth + 1);
+            self.loads[elf] = old;
+        }
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for m in &self.moves {
+            writeln!(
+                f,
+                "Move {} calories from elf {} to elf {}",
+                m.calories, m.from, m.to
+            )?;
+        }
+        writeln!(
+            f,
+            "{} moves, heaviest elf now carries {} calories",
+            self.moves.len(),
+            self.max_load()
+        )?;
+        for (elf, load) in self.loads.iter().enumerate() {
+            writeln!(f, "Elf {elf}: {load}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rebalance::{plan, Move, Planner};
+    use crate::{Error, Width};
+
+    #[test]
+    fn test_greedy() {
+        let elves = vec![vec![3, 3, 2, 2, 2], vec![]];
+        let p = plan(&elves, Planner::Greedy, Width::I128).unwrap();
+        assert_eq!(p.loads, vec![6, 6]);
+        assert_eq!(p.moves.len(), 2);
+
+        // already balanced, nothing to do
+        let elves = vec![vec![5], vec![2, 3]];
+        let p = plan(&elves, Planner::Greedy, Width::I128).unwrap();
+        assert!(p.moves.is_empty());
+        assert_eq!(p.loads, vec![5, 5]);
+    }
+
+    #[test]
+    fn test_exact_beats_greedy() {
+        // greedy can't move anything here since only a swap helps
+        let elves = vec![vec![5, 4], vec![3, 3]];
+        let p = plan(&elves, Planner::Greedy, Width::I128).unwrap();
+        assert_eq!(p.max_load(), 9);
+
+        let p = plan(&elves, Planner::Exact, Width::I128).unwrap();
+        assert_eq!(p.max_load(), 8);
+        assert_eq!(
+            p.moves,
+            vec![
+                Move {
+                    calories: 4,
+                    from: 0,
+                    to: 1
+                },
+                Move {
+                    calories: 3,
+                    from: 1,
+                    to: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exact_limit() {
+        let elves = vec![vec![1; 30], vec![]];
+        assert!(matches!(
+            plan(&elves, Planner::Exact, Width::I128),
+            Err(Error::TooManyItems { items: 30, .. })
+        ));
+        assert_eq!(
+            plan(&elves, Planner::Greedy, Width::I128).unwrap().loads,
+            vec![15, 15]
+        );
+        // 10 elves with 2 items each is fine by item count alone
+        let elves = vec![vec![7, 3]; 10];
+        assert!(plan(&elves, Planner::Exact, Width::I128).is_err());
+    }
+
+    #[test]
+    fn test_exact_at_limit() {
+        // 4^10 is right at the limit, uneven enough that greedy's start
+        // isn't already perfect
+        let elves = vec![vec![9, 8, 7, 7, 6], vec![5, 4, 3], vec![2, 1], vec![]];
+        let p = plan(&elves, Planner::Exact, Width::I128).unwrap();
+        assert_eq!(p.max_load(), 13);
+    }
+
+    #[test]
+    fn test_width() {
+        // an elf already too heavy for the width can't be planned for
+        let elves = vec![vec![3_000_000_000], vec![1]];
+        for planner in [Planner::Greedy, Planner::Exact] {
+            assert!(matches!(
+                plan(&elves, planner, Width::I32),
+                Err(Error::Overflow { group: 0, .. })
+            ));
+        }
+        // loads past i32::MAX come up along the way but never in the plan
+        let elves = vec![vec![i32::MAX as i128 - 5, 4], vec![1, 1]];
+        let p = plan(&elves, Planner::Exact, Width::I32).unwrap();
+        assert_eq!(p.loads, vec![i32::MAX as i128 - 5, 6]);
+    }
+}
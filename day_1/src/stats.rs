@@ -0,0 +1,167 @@
+use std::fmt::{self, Display};
+
+/// percentiles reported alongside the median
+const PERCENTILES: [f64; 5] = [10.0, 25.0, 75.0, 90.0, 99.0];
+/// length of the longest histogram bar
+const BAR_WIDTH: usize = 40;
+
+/// how the calories are spread across the elves
+#[derive(Debug, Default, PartialEq)]
+pub struct LoadStats {
+    pub count: usize,
+    pub min: i128,
+    pub max: i128,
+    pub mean: f64,
+    pub median: f64,
+    /// population standard deviation
+    pub std_dev: f64,
+    /// (percentile, value) pairs, interpolated between the nearest loads
+    pub percentiles: Vec<(f64, f64)>,
+    /// (low, high, count) for each bin, bounds are inclusive
+    pub histogram: Vec<(i128, i128, usize)>,
+}
+
+/// crunches the (position, total) pairs from `elf_loads` into a
+/// `LoadStats`, with the histogram split into at most `bins` bins
+pub fn load_stats(loads: &[(usize, i128)], bins: usize) -> LoadStats {
+    let mut sorted: Vec<i128> = loads.iter().map(|l| l.1).collect();
+    sorted.sort();
+    let (min, max) = match (sorted.first(), sorted.last()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return LoadStats::default(),
+    };
+
+    let count = sorted.len();
+    let mean = sorted.iter().map(|&l| l as f64).sum::<f64>() / count as f64;
+    let variance = sorted
+        .iter()
+        .map(|&l| (l as f64 - mean).powi(2))
+        .sum::<f64>()
+        / count as f64;
+
+    LoadStats {
+        count,
+        min,
+        max,
+        mean,
+        median: percentile(&sorted, 50.0),
+        std_dev: variance.sqrt(),
+        percentiles: PERCENTILES
+            .iter()
+            .map(|&p| (p, percentile(&sorted, p)))
+            .collect(),
+        histogram: histogram(&sorted, bins),
+    }
+}
+
+/// linear interpolation between the two closest ranks, `sorted` can't be empty
+fn percentile(sorted: &[i128], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let frac = rank - lower as f64;
+    sorted[lower] as f64 + (sorted[upper] as f64 - sorted[lower] as f64) * frac
+}
+
+/// equal width bins covering min..=max, `sorted` can't be empty
+fn histogram(sorted: &[i128], bins: usize) -> Vec<(i128, i128, usize)> {
+    if bins == 0 {
+        return Vec::new();
+    }
+    let min = sorted[0];
+    let span = sorted[sorted.len() - 1].abs_diff(min);
+    // ceil((span + 1) / bins) without overflowing on span + 1
+    let bin_width = span / bins as u128 + 1;
+
+    let mut out: Vec<(i128, i128, usize)> = Vec::new();
+    for &load in sorted {
+        let bin = (load.abs_diff(min) / bin_width) as usize;
+        // loads are sorted so we only ever need to add bins at the end
+        while out.len() <= bin {
+            let low = min.saturating_add_unsigned(out.len() as u128 * bin_width);
+            let high = low.saturating_add_unsigned(bin_width - 1);
+            out.push((low, high, 0));
+        }
+        out[bin].2 += 1;
+    }
+    out
+}
+
+impl Display for LoadStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.count == 0 {
+            return writeln!(f, "No Elves found");
+        }
+        writeln!(f, "Elves:      {}", self.count)?;
+        writeln!(f, "Min:        {}", self.min)?;
+        writeln!(f, "Max:        {}", self.max)?;
+        writeln!(f, "Mean:       {:.1}", self.mean)?;
+        writeln!(f, "Median:     {:.1}", self.median)?;
+        writeln!(f, "Std dev:    {:.1}", self.std_dev)?;
+        for (p, value) in &self.percentiles {
+            writeln!(f, "{:<12}{:.1}", format!("p{p}:"), value)?;
+        }
+
+        let most = self.histogram.iter().map(|b| b.2).max().unwrap_or(0);
+        let label_width = self
+            .histogram
+            .iter()
+            .map(|b| b.0.to_string().len().max(b.1.to_string().len()))
+            .max()
+            .unwrap_or(0);
+        for (low, high, count) in &self.histogram {
+            // round up so a bin with anything in it always shows
+            let bar = (count * BAR_WIDTH).div_ceil(most);
+            writeln!(
+                f,
+                "{:>w$} - {:>w$} | {:<BAR_WIDTH$} {}",
+                low,
+                high,
+                "#".repeat(bar),
+                count,
+                w = label_width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::stats::{histogram, load_stats, percentile, LoadStats};
+
+    #[test]
+    fn test_percentile() {
+        let sorted = [10, 20, 30, 40];
+        assert_eq!(percentile(&sorted, 0.0), 10.0);
+        assert_eq!(percentile(&sorted, 50.0), 25.0);
+        assert_eq!(percentile(&sorted, 100.0), 40.0);
+        assert_eq!(percentile(&[7], 90.0), 7.0);
+    }
+
+    #[test]
+    fn test_histogram() {
+        assert_eq!(histogram(&[0, 1, 5, 9], 2), vec![(0, 4, 2), (5, 9, 2)]);
+        assert_eq!(histogram(&[3, 3, 3], 4), vec![(3, 3, 3)]);
+        assert_eq!(
+            histogram(&[0, 10], 3),
+            vec![(0, 3, 1), (4, 7, 0), (8, 11, 1)]
+        );
+        assert_eq!(histogram(&[0, 10], 0), vec![]);
+    }
+
+    #[test]
+    fn test_load_stats() {
+        let loads = vec![(0, 6000), (1, 4000), (2, 11000), (3, 24000), (4, 10000)];
+        let stats = load_stats(&loads, 2);
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.min, 4000);
+        assert_eq!(stats.max, 24000);
+        assert_eq!(stats.mean, 11000.0);
+        assert_eq!(stats.median, 10000.0);
+        assert!((stats.std_dev - 6985.7).abs() < 0.1);
+        assert_eq!(stats.histogram, vec![(4000, 14000, 4), (14001, 24001, 1)]);
+
+        assert_eq!(load_stats(&[], 10), LoadStats::default());
+    }
+}
@@ -0,0 +1,91 @@
+use std::fmt::{self, Display};
+
+use crate::rules::{Rules, Throw};
+use crate::score_round;
+use crate::scoring::Scoring;
+
+/// how good the guide really is, looked at a few different ways
+#[derive(Debug, PartialEq)]
+pub struct Analysis {
+    pub rounds: usize,
+    pub actual_p1: i64,
+    pub actual_p2: i64,
+    /// if the opponent threw uniformly at random instead
+    pub expected_p1: f64,
+    pub expected_p2: f64,
+    /// knowing the opponent's throws in advance
+    pub best: i64,
+}
+
+/// `rounds` holds (opponent, part 1 throw, part 2 throw) for every round
+pub fn analyze(rules: &Rules, scoring: &Scoring, rounds: &[(Throw, Throw, Throw)]) -> Analysis {
+    let shapes: Vec<Throw> = (0..rules.len()).map(|s| rules.throw(s)).collect();
+    let mut analysis = Analysis {
+        rounds: rounds.len(),
+        actual_p1: 0,
+        actual_p2: 0,
+        expected_p1: 0.0,
+        expected_p2: 0.0,
+        best: 0,
+    };
+
+    for &(opp, p1, p2) in rounds {
+        analysis.actual_p1 += score_round(scoring, p1, opp) as i64;
+        analysis.actual_p2 += score_round(scoring, p2, opp) as i64;
+
+        // part 1 is a fixed throw whatever the opponent does, part 2 is a
+        // fixed outcome so our throw follows theirs
+        let outcome = p2.outcome_against(opp);
+        let (mut sum_p1, mut sum_p2) = (0, 0);
+        for &r in &shapes {
+            sum_p1 += score_round(scoring, p1, r);
+            sum_p2 += score_round(scoring, rules.throw_for(r, outcome), r);
+        }
+        analysis.expected_p1 += sum_p1 as f64 / shapes.len() as f64;
+        analysis.expected_p2 += sum_p2 as f64 / shapes.len() as f64;
+
+        analysis.best += shapes
+            .iter()
+            .map(|&t| score_round(scoring, t, opp))
+            .max()
+            .unwrap_or(0) as i64;
+    }
+    analysis
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Rounds:                      {}", self.rounds)?;
+        writeln!(f, "Actual score (Part 1):       {}", self.actual_p1)?;
+        writeln!(f, "Actual score (Part 2):       {}", self.actual_p2)?;
+        writeln!(f, "Expected vs random (Part 1): {:.1}", self.expected_p1)?;
+        writeln!(f, "Expected vs random (Part 2): {:.1}", self.expected_p2)?;
+        writeln!(f, "Best possible:               {}", self.best)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::analysis::analyze;
+    use crate::rules::Rules;
+    use crate::scoring::Scoring;
+
+    #[test]
+    fn test_analyze() {
+        let rules = Rules::default();
+        let scoring = Scoring::new(&rules);
+        let t = |s| rules.throw(s);
+        // the example guide: A Y, B X, C Z
+        let rounds = vec![(t(0), t(1), t(0)), (t(1), t(0), t(0)), (t(2), t(2), t(0))];
+        let a = analyze(&rules, &scoring, &rounds);
+
+        assert_eq!(a.actual_p1, 15);
+        assert_eq!(a.actual_p2, 12);
+        // a fixed throw averages its value plus (6 + 3 + 0) / 3
+        assert_eq!(a.expected_p1, (2.0 + 3.0) + (1.0 + 3.0) + (3.0 + 3.0));
+        // a draw averages (1 + 2 + 3) / 3 + 3, a loss just the value
+        assert_eq!(a.expected_p2, 5.0 + 2.0 + 8.0);
+        // paper, scissors, rock
+        assert_eq!(a.best, 8 + 9 + 7);
+    }
+}
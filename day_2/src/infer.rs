@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use crate::rules::{Outcome, Rules};
+use crate::scoring::Scoring;
+use crate::{parse_opponent, score_round, split_round, Error};
+
+pub const OUTCOMES: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+
+/// how the second column of the guide is read
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Reading {
+    /// the throw to make, like `parse_round_as_throws`
+    Throws,
+    /// how the round should end, like `parse_round_as_result`
+    Result,
+}
+
+/// one way of decoding the second column, along with the total it gives
+#[derive(Debug, PartialEq)]
+pub struct Candidate {
+    pub reading: Reading,
+    /// (code, shape or outcome name) pairs, in code order
+    pub key: Vec<(String, String)>,
+    pub score: i32,
+    /// whether the score matches the observed total
+    pub consistent: bool,
+}
+
+/// every candidate key for a guide, plus how many codes it had to map
+#[derive(Debug, PartialEq)]
+pub struct Inference {
+    /// ranked from highest score to lowest
+    pub candidates: Vec<Candidate>,
+    /// distinct second column codes, with more than there are outcomes
+    /// none of the candidates read the guide as results
+    pub codes: usize,
+}
+
+/// tries every mapping of the guide's second column codes onto shapes and
+/// onto outcomes, each code getting a different one
+///
+/// malformed rounds, and rounds bringing in more codes than there are shapes,
+/// are all reported unless `lenient` is set, then they're skipped
+pub fn infer_key(
+    rules: &Rules,
+    scoring: &Scoring,
+    guide: &str,
+    observed: i32,
+    lenient: bool,
+) -> Result<Inference, Vec<Error>> {
+    // tally up (opponent shape, code) pairs so each mapping is cheap to score
+    let mut codes: Vec<&str> = Vec::new();
+    let mut counts: HashMap<(usize, usize), i32> = HashMap::new();
+    let mut errors = Vec::new();
+    for (line, round) in guide.split_terminator('\n').enumerate() {
+        let parsed = split_round(line + 1, round)
+            .and_then(|(opp, code)| Ok((parse_opponent(rules, line + 1, opp)?, code)));
+        let (opp, code) = match parsed {
+            Ok(p) => p,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        let code = match codes.iter().position(|c| *c == code) {
+            Some(c) => c,
+            // no mapping can give every code its own shape
+            None if codes.len() == rules.len() => {
+                errors.push(Error::TooManyCodes {
+                    line: line + 1,
+                    token: 2,
+                    code: code.to_string(),
+                    shapes: rules.len(),
+                });
+                continue;
+            }
+            None => {
+                codes.push(code);
+                codes.len() - 1
+            }
+        };
+        *counts.entry((opp.index(), code)).or_default() += 1;
+    }
+    if !errors.is_empty() && !lenient {
+        return Err(errors);
+    }
+    // keep the output stable regardless of which code shows up first
+    let mut order: Vec<usize> = (0..codes.len()).collect();
+    order.sort_by_key(|&c| codes[c]);
+
+    let mut out = Vec::new();
+    for targets in arrangements(rules.len(), codes.len()) {
+        let score = counts
+            .iter()
+            .map(|(&(opp, code), n)| {
+                n * score_round(scoring, rules.throw(targets[code]), rules.throw(opp))
+            })
+            .sum();
+        out.push(Candidate {
+            reading: Reading::Throws,
+            key: order
+                .iter()
+                .map(|&c| {
+                    let name = rules.name(rules.throw(targets[c]));
+                    (codes[c].to_string(), name.to_string())
+                })
+                .collect(),
+            score,
+            consistent: score == observed,
+        });
+    }
+    for targets in arrangements(OUTCOMES.len(), codes.len()) {
+        let score = counts
+            .iter()
+            .map(|(&(opp, code), n)| {
+                let opp = rules.throw(opp);
+                n * score_round(scoring, rules.throw_for(opp, OUTCOMES[targets[code]]), opp)
+            })
+            .sum();
+        out.push(Candidate {
+            reading: Reading::Result,
+            key: order
+                .iter()
+                .map(|&c| (codes[c].to_string(), OUTCOMES[targets[c]].to_string()))
+                .collect(),
+            score,
+            consistent: score == observed,
+        });
+    }
+    out.sort_by_key(|c| std::cmp::Reverse(c.score));
+    Ok(Inference {
+        candidates: out,
+        codes: codes.len(),
+    })
+}
+
+/// every way of picking `k` distinct items out of `n`, in order
+fn arrangements(n: usize, k: usize) -> Arrangements {
+    Arrangements {
+        n,
+        next: (k <= n).then(|| (0..k).collect()),
+    }
+}
+
+/// works the arrangements out one at a time, there are n!/(n-k)! of them
+struct Arrangements {
+    n: usize,
+    next: Option<Vec<usize>>,
+}
+
+impl Iterator for Arrangements {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Vec<usize>> {
+        let current = self.next.take()?;
+        // bump the last item that has a bigger unused one to go to, then
+        // fill in after it with the smallest unused items
+        let mut a = current.clone();
+        while let Some(last) = a.pop() {
+            if let Some(bigger) = (last + 1..self.n).find(|i| !a.contains(i)) {
+                a.push(bigger);
+                for i in 0..self.n {
+                    if a.len() == current.len() {
+                        break;
+                    }
+                    if !a.contains(&i) {
+                        a.push(i);
+                    }
+                }
+                self.next = Some(a);
+                break;
+            }
+        }
+        Some(current)
+    }
+}
+
+impl Display for Reading {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reading::Throws => write!(f, "throws"),
+            Reading::Result => write!(f, "result"),
+        }
+    }
+}
+
+impl Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key: Vec<String> = self.key.iter().map(|(c, t)| format!("{c}={t}")).collect();
+        write!(f, "{:>6} ({}) {}", self.score, self.reading, key.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::infer::{arrangements, infer_key, Reading};
+    use crate::rules::Rules;
+    use crate::scoring::Scoring;
+    use crate::Error;
+
+    #[test]
+    fn test_arrangements() {
+        assert_eq!(
+            arrangements(3, 0).collect::<Vec<_>>(),
+            vec![Vec::<usize>::new()]
+        );
+        assert_eq!(
+            arrangements(3, 2).collect::<Vec<_>>(),
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![1, 0],
+                vec![1, 2],
+                vec![2, 0],
+                vec![2, 1]
+            ]
+        );
+        assert_eq!(arrangements(3, 3).count(), 6);
+        assert_eq!(arrangements(5, 3).count(), 60);
+        assert_eq!(arrangements(2, 3).count(), 0);
+    }
+
+    #[test]
+    fn test_infer_key() {
+        let rules = Rules::default();
+        let scoring = Scoring::new(&rules);
+        let key = |pairs: [(&str, &str); 3]| {
+            pairs
+                .iter()
+                .map(|(c, t)| (c.to_string(), t.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        // the example is small enough that lots of keys fit
+        let found = infer_key(&rules, &scoring, include_str!("../test.txt"), 15, false)
+            .unwrap()
+            .candidates;
+        assert_eq!(found.len(), 12);
+        assert_eq!(found.iter().filter(|c| c.consistent).count(), 8);
+        assert!(found
+            .iter()
+            .any(|c| c.consistent
+                && c.key == key([("X", "Rock"), ("Y", "Paper"), ("Z", "Scissors")])));
+        // ranked highest first
+        assert!(found.windows(2).all(|w| w[0].score >= w[1].score));
+
+        // but the full guide pins it down
+        let found = infer_key(&rules, &scoring, include_str!("../input.txt"), 14204, false)
+            .unwrap()
+            .candidates;
+        let consistent: Vec<_> = found.iter().filter(|c| c.consistent).collect();
+        assert_eq!(consistent.len(), 1);
+        assert_eq!(consistent[0].reading, Reading::Result);
+        assert_eq!(
+            consistent[0].key,
+            key([("X", "lose"), ("Y", "draw"), ("Z", "win")])
+        );
+    }
+
+    #[test]
+    fn test_infer_more_codes_than_outcomes() {
+        let rules: Rules = include_str!("../rpsls.txt").parse().unwrap();
+        let scoring = Scoring::new(&rules);
+        let guide = "A V\nB W\nC X\nD Y\nE Z\n";
+        let inference = infer_key(&rules, &scoring, guide, 30, false).unwrap();
+        // five codes can't go one each onto three outcomes, only throws work
+        assert_eq!(inference.codes, 5);
+        assert_eq!(inference.candidates.len(), 120);
+        assert!(inference
+            .candidates
+            .iter()
+            .all(|c| c.reading == Reading::Throws));
+        assert_eq!(
+            infer_key(&rules, &scoring, "A X\nB Y\nC Z\n", 30, false)
+                .unwrap()
+                .candidates
+                .len(),
+            60 + 6
+        );
+    }
+
+    #[test]
+    fn test_infer_too_many_codes() {
+        let rules = Rules::default();
+        let scoring = Scoring::new(&rules);
+        let guide = "A X\nB Y\nC Z\nC Q\nD X\nA W\n";
+        let errors = infer_key(&rules, &scoring, guide, 15, false).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            errors[0],
+            Error::TooManyCodes {
+                line: 4,
+                token: 2,
+                shapes: 3,
+                ..
+            }
+        ));
+        assert!(matches!(errors[1], Error::UnknownThrow { line: 5, .. }));
+        assert!(matches!(errors[2], Error::TooManyCodes { line: 6, .. }));
+        // skipping the extra code leaves the same keys as without it
+        let found = infer_key(&rules, &scoring, guide, 15, true)
+            .unwrap()
+            .candidates;
+        assert_eq!(found.len(), 12);
+        assert!(found.iter().all(|c| c.key.len() == 3));
+    }
+}
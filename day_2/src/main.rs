@@ -1,149 +1,483 @@
-use clap::Parser;
-use enum_primitive_derive::Primitive;
-use num_traits::ToPrimitive;
-use std::cmp::Ordering;
-use std::fs;
-use std::str::FromStr;
+use clap::{Parser, Subcommand};
+use std::{
+    fs,
+    io::{self, BufWriter},
+    process,
+};
 use thiserror::Error;
 
+mod analysis;
+mod infer;
+mod play;
+mod rules;
+mod scoring;
+mod simulate;
+mod strategy;
+use rules::{Outcome, Rules, Throw};
+use scoring::Scoring;
+use simulate::run_tournament;
+use strategy::{Frequency, Guide, Kind, Markov, Rng, Strategy, Uniform};
+
 #[derive(Parser, Debug)]
 #[command(
     author = "David Miller",
     version = "v1.0.0",
-    about = "Advent of Code (Day 2)"
+    about = "Advent of Code (Day 2)",
+    subcommand_negates_reqs = true
 )]
-
 struct Cli {
+    /// strategy guide, not needed when playing
+    #[clap(short, long, required = true)]
+    input: Option<String>,
+    /// file describing the shapes to play with, defaults to Rock-Paper-Scissors
+    #[clap(short, long)]
+    rules: Option<String>,
+    /// file with outcome points and shape values, defaults to 6/3/0 and 1, 2, 3...
     #[clap(short, long)]
-    input: String,
+    scoring: Option<String>,
+    /// points for a win (overrides the scoring file)
+    #[clap(long)]
+    win: Option<i32>,
+    /// points for a draw (overrides the scoring file)
+    #[clap(long)]
+    draw: Option<i32>,
+    /// points for a loss (overrides the scoring file)
+    #[clap(long)]
+    lose: Option<i32>,
+    /// shape value as NAME=POINTS, can be repeated (overrides the scoring file)
+    #[clap(long = "value", value_name = "NAME=POINTS")]
+    values: Vec<String>,
+    /// work out the second column's key from an observed total score
+    #[clap(long, value_name = "SCORE")]
+    infer: Option<i32>,
+    /// play the built-in strategies against each other for this many rounds
+    #[clap(long, value_name = "ROUNDS")]
+    simulate: Option<usize>,
+    /// seed for the random strategies
+    #[clap(long, default_value_t = 2022)]
+    seed: u64,
+    /// skip malformed rounds instead of bailing out
+    #[clap(long)]
+    lenient: bool,
+    /// print every round's throws, outcome and points for both parts
+    #[clap(long)]
+    trace: bool,
+    /// compare the guide against a random opponent and the best possible play
+    #[clap(long)]
+    analyze: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// play against the computer on the terminal
+    Play {
+        /// how the computer picks its throws
+        #[clap(short, long, value_enum, default_value_t = Kind::Markov)]
+        computer: Kind,
+        /// where to write the rounds played, in the strategy guide format
+        #[clap(short, long, default_value = "transcript.txt")]
+        transcript: String,
+    },
 }
 fn main() {
     let cli_args = Cli::parse();
 
-    let input = &fs::read_to_string(cli_args.input).unwrap();
+    let rules = match &cli_args.rules {
+        Some(path) => match fs::read_to_string(path).unwrap().parse() {
+            Ok(rules) => rules,
+            Err(e) => fail(&[e]),
+        },
+        None => Rules::default(),
+    };
+    let scoring = match load_scoring(&cli_args, &rules) {
+        Ok(scoring) => scoring,
+        Err(e) => fail(&[e]),
+    };
+
+    if let Some(Command::Play {
+        computer,
+        transcript,
+    }) = &cli_args.command
+    {
+        let mut rng = Rng::new(cli_args.seed);
+        let rounds = play::play(
+            &rules,
+            &scoring,
+            computer.build().as_mut(),
+            &mut rng,
+            io::stdin().lock(),
+            BufWriter::new(io::stdout()),
+        )
+        .unwrap();
+        fs::write(transcript, play::transcript(&rules, &rounds)).unwrap();
+        println!("Wrote {} rounds to {transcript}", rounds.len());
+        return;
+    }
+    let input = &fs::read_to_string(cli_args.input.as_ref().unwrap()).unwrap();
+
+    if let Some(observed) = cli_args.infer {
+        let inference = match infer::infer_key(&rules, &scoring, input, observed, cli_args.lenient)
+        {
+            Ok(inference) => inference,
+            Err(errors) => fail(&errors),
+        };
+        if inference.codes > infer::OUTCOMES.len() {
+            println!(
+                "Can't read the guide as results, {} codes but only {} outcomes",
+                inference.codes,
+                infer::OUTCOMES.len()
+            );
+        }
+        let found = inference.candidates;
+        let consistent: Vec<_> = found.iter().filter(|c| c.consistent).collect();
+        println!(
+            "{} of {} mappings give a score of {observed}",
+            consistent.len(),
+            found.len()
+        );
+        if consistent.is_empty() {
+            // nothing fits exactly, show the nearest misses instead
+            let mut closest: Vec<_> = found.iter().collect();
+            closest.sort_by_key(|c| (c.score - observed).abs());
+            println!("Closest:");
+            closest.iter().take(3).for_each(|c| println!("{c}"));
+        }
+        for c in consistent {
+            println!("{c}");
+        }
+        return;
+    }
+
+    if let Some(rounds) = cli_args.simulate {
+        let mut guide = Vec::new();
+        let mut errors = Vec::new();
+        for (line, round) in input.split_terminator('\n').enumerate() {
+            match parse_round_as_throws(&rules, line + 1, round) {
+                Ok(throws) => guide.push(throws[1]),
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() && !cli_args.lenient {
+            fail(&errors);
+        }
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Uniform),
+            Box::new(Frequency::default()),
+            Box::new(Markov::default()),
+            Box::new(Guide::new(guide)),
+        ];
+        let mut rng = Rng::new(cli_args.seed);
+        print!(
+            "{}",
+            run_tournament(&rules, &scoring, &mut strategies, rounds, &mut rng)
+        );
+        return;
+    }
+
     let rounds = input.split_terminator('\n');
 
     let mut sum_p1 = 0;
     let mut sum_p2 = 0;
-    for round in rounds {
-        let throws_p1 = parse_round_as_throws(round); //parser for part 1
-        let throws_p2 = parse_round_as_result(round); // parser for part 2
-        let score_p1 = score_round(throws_p1[1], throws_p1[0]);
-        let score_p2 = score_round(throws_p2[1], throws_p2[0]);
-        sum_p1 += score_p1;
-        sum_p2 += score_p2;
+    let mut errors = Vec::new();
+    let (mut skipped_p1, mut skipped_p2) = (0, 0);
+    let mut traced = Vec::new();
+    let mut analyzed = Vec::new();
+    for (line, round) in rounds.enumerate() {
+        // a round can be fine for one part and not the other, so each
+        // part skips (or reports) its own bad rounds
+        // parser for part 1
+        let throws_p1 = match parse_round_as_throws(&rules, line + 1, round) {
+            Ok(throws_p1) => {
+                sum_p1 += score_round(&scoring, throws_p1[1], throws_p1[0]);
+                Some(throws_p1)
+            }
+            Err(e) => {
+                skipped_p1 += 1;
+                errors.push(e);
+                None
+            }
+        };
+        // parser for part 2
+        let throws_p2 = match parse_round_as_result(&rules, line + 1, round) {
+            Ok(throws_p2) => {
+                sum_p2 += score_round(&scoring, throws_p2[1], throws_p2[0]);
+                Some(throws_p2)
+            }
+            Err(e) => {
+                skipped_p2 += 1;
+                // anything else is wrong with the round for part 1 too,
+                // and has already been reported
+                if matches!(e, Error::UnknownOutcome { .. }) {
+                    errors.push(e);
+                }
+                None
+            }
+        };
+
+        if cli_args.trace {
+            traced.push(format!(
+                "{:>5} {:<6}| Part 1: {} | Part 2: {}",
+                line + 1,
+                round,
+                trace_round(&rules, &scoring, throws_p1.as_deref()),
+                trace_round(&rules, &scoring, throws_p2.as_deref())
+            ));
+        }
+        if let (Some(p1), Some(p2)) = (&throws_p1, &throws_p2) {
+            analyzed.push((p1[0], p1[1], p2[1]));
+        }
     }
+    if !errors.is_empty() {
+        if !cli_args.lenient {
+            fail(&errors);
+        }
+        eprintln!("Skipped {skipped_p1} bad rounds in part 1 and {skipped_p2} in part 2");
+    }
+    traced.iter().for_each(|t| println!("{t}"));
     println!("Final score (Part 1): {sum_p1}");
     println!("Final score (Part 2): {sum_p2}");
+    if cli_args.analyze {
+        print!("{}", analysis::analyze(&rules, &scoring, &analyzed));
+    }
 }
 
-#[derive(Eq, PartialEq, Primitive, Copy, Clone)]
-enum Throw {
-    Rock = 1,
-    Paper = 2,
-    Scissors = 3,
+/// one part of a round for `--trace`, throws are (opponent, you)
+fn trace_round(rules: &Rules, scoring: &Scoring, throws: Option<&[Throw]>) -> String {
+    match throws {
+        Some(&[opp, you]) => format!(
+            "{} vs {}, {}, {} points",
+            rules.name(you),
+            rules.name(opp),
+            you.outcome_against(opp),
+            score_round(scoring, you, opp)
+        ),
+        _ => "skipped".to_string(),
+    }
+}
+
+fn fail(errors: &[Error]) -> ! {
+    for e in errors {
+        eprintln!("{e}");
+    }
+    if errors.len() > 1 {
+        eprintln!(
+            "error: found {} problems, use --lenient to skip bad rounds",
+            errors.len()
+        );
+    }
+    process::exit(1);
 }
 
 #[derive(Error, Debug)]
-enum Error {
+pub enum Error {
     #[error("failed to parse throw, invalid input")]
     InvalidInput,
+    #[error("invalid rules on line {line}: {reason}")]
+    InvalidRules { line: usize, reason: String },
+    #[error("rules need an odd number of shapes (at least 3), found {0}")]
+    ShapeCount(usize),
+    #[error("rules need a code for each of lose, draw and win")]
+    MissingOutcome,
+    #[error("invalid scoring on line {line}: {reason}")]
+    InvalidScoring { line: usize, reason: String },
+    #[error("invalid shape value {0:?}, expected NAME=POINTS")]
+    InvalidValue(String),
+    #[error("no shape called {0:?} in the rules")]
+    UnknownShape(String),
+    #[error("line {line}, token {token}: missing code")]
+    MissingToken { line: usize, token: usize },
+    #[error("line {line}, token {token}: unexpected extra code")]
+    ExtraToken { line: usize, token: usize },
+    #[error("line {line}, token {token}: {code:?} isn't a throw in the rules")]
+    UnknownThrow {
+        line: usize,
+        token: usize,
+        code: String,
+    },
+    #[error("line {line}, token {token}: {code:?} is one code more than the {shapes} shapes in the rules")]
+    TooManyCodes {
+        line: usize,
+        token: usize,
+        code: String,
+        shapes: usize,
+    },
+    #[error("line {line}, token {token}: {code:?} isn't an outcome in the rules")]
+    UnknownOutcome {
+        line: usize,
+        token: usize,
+        code: String,
+    },
 }
 
-impl PartialOrd for Throw {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self {
-            Throw::Rock => match other {
-                Throw::Rock => Some(Ordering::Equal),
-                Throw::Paper => Some(Ordering::Less),
-                Throw::Scissors => Some(Ordering::Greater),
-            },
-            Throw::Paper => match other {
-                Throw::Rock => Some(Ordering::Greater),
-                Throw::Paper => Some(Ordering::Equal),
-                Throw::Scissors => Some(Ordering::Less),
-            },
-            Throw::Scissors => match other {
-                Throw::Rock => Some(Ordering::Less),
-                Throw::Paper => Some(Ordering::Greater),
-                Throw::Scissors => Some(Ordering::Equal),
-            },
-        }
+/// scoring file first (if any), then whatever was set on the command line
+fn load_scoring(cli_args: &Cli, rules: &Rules) -> Result<Scoring, Error> {
+    let mut scoring = match &cli_args.scoring {
+        Some(path) => Scoring::parse(&fs::read_to_string(path).unwrap(), rules)?,
+        None => Scoring::new(rules),
+    };
+    if let Some(win) = cli_args.win {
+        scoring.win = win;
     }
+    if let Some(draw) = cli_args.draw {
+        scoring.draw = draw;
+    }
+    if let Some(lose) = cli_args.lose {
+        scoring.lose = lose;
+    }
+    for v in &cli_args.values {
+        let (name, points) = v
+            .split_once('=')
+            .ok_or_else(|| Error::InvalidValue(v.clone()))?;
+        let points = points.parse().map_err(|_| Error::InvalidValue(v.clone()))?;
+        scoring.set_value(rules, name, points)?;
+    }
+    Ok(scoring)
 }
 
-impl FromStr for Throw {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "A" | "X" => Ok(Throw::Rock),
-            "B" | "Y" => Ok(Throw::Paper),
-            "C" | "Z" => Ok(Throw::Scissors),
-            _ => Err(Error::InvalidInput),
-        }
+/// splits a round into its two codes, `line` is only used for errors
+fn split_round(line: usize, s: &str) -> Result<(&str, &str), Error> {
+    let mut tokens = s.split_whitespace();
+    let opp = tokens
+        .next()
+        .ok_or(Error::MissingToken { line, token: 1 })?;
+    let you = tokens
+        .next()
+        .ok_or(Error::MissingToken { line, token: 2 })?;
+    if tokens.next().is_some() {
+        return Err(Error::ExtraToken { line, token: 3 });
     }
+    Ok((opp, you))
 }
 
-fn parse_round_as_throws(s: &str) -> Vec<Throw> {
-    s.split_terminator(' ')
-        .map(|t| Throw::from_str(t).unwrap())
-        .collect()
+/// first column of a round
+fn parse_opponent(rules: &Rules, line: usize, code: &str) -> Result<Throw, Error> {
+    rules.opponent_throw(code).map_err(|_| Error::UnknownThrow {
+        line,
+        token: 1,
+        code: code.to_string(),
+    })
 }
 
-fn parse_round_as_result(s: &str) -> Vec<Throw> {
-    let entries: Vec<&str> = s.split_terminator(' ').collect();
-    vec![
-        Throw::from_str(entries[0]).unwrap(),
-        get_desired_throw(Throw::from_str(entries[0]).unwrap(), entries[1]).unwrap(),
-    ]
+fn parse_round_as_throws(rules: &Rules, line: usize, s: &str) -> Result<Vec<Throw>, Error> {
+    let (opp, you) = split_round(line, s)?;
+    Ok(vec![
+        parse_opponent(rules, line, opp)?,
+        rules.player_throw(you).map_err(|_| Error::UnknownThrow {
+            line,
+            token: 2,
+            code: you.to_string(),
+        })?,
+    ])
 }
 
-fn get_desired_throw(t: Throw, result: &str) -> Result<Throw, Error> {
-    match result {
-        "X" => match t {
-            Throw::Rock => Ok(Throw::Scissors),
-            Throw::Paper => Ok(Throw::Rock),
-            Throw::Scissors => Ok(Throw::Paper),
-        },
-        "Y" => match t {
-            Throw::Rock => Ok(Throw::Rock),
-            Throw::Paper => Ok(Throw::Paper),
-            Throw::Scissors => Ok(Throw::Scissors),
-        },
-        "Z" => match t {
-            Throw::Rock => Ok(Throw::Paper),
-            Throw::Paper => Ok(Throw::Scissors),
-            Throw::Scissors => Ok(Throw::Rock),
-        },
-        _ => Err(Error::InvalidInput),
-    }
+fn parse_round_as_result(rules: &Rules, line: usize, s: &str) -> Result<Vec<Throw>, Error> {
+    let (opp, result) = split_round(line, s)?;
+    let opp = parse_opponent(rules, line, opp)?;
+    Ok(vec![
+        opp,
+        get_desired_throw(rules, opp, result).map_err(|_| Error::UnknownOutcome {
+            line,
+            token: 2,
+            code: result.to_string(),
+        })?,
+    ])
 }
 
-fn score_round(you: Throw, opp: Throw) -> i32 {
+fn get_desired_throw(rules: &Rules, t: Throw, result: &str) -> Result<Throw, Error> {
+    Ok(rules.throw_for(t, rules.outcome(result)?))
+}
+
+fn score_round(scoring: &Scoring, you: Throw, opp: Throw) -> i32 {
+    let value = scoring.value(you);
     if you > opp {
-        you.to_i32().unwrap() + 6
+        value + scoring.outcome_points(Outcome::Win)
     } else if you < opp {
-        you.to_i32().unwrap()
+        value + scoring.outcome_points(Outcome::Lose)
     } else {
-        you.to_i32().unwrap() + 3
+        value + scoring.outcome_points(Outcome::Draw)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{score_round, Throw};
+    use crate::rules::Rules;
+    use crate::scoring::Scoring;
+    use crate::{parse_round_as_result, parse_round_as_throws, score_round, Error};
 
     #[test]
     fn test_score_round() {
-        assert_eq!(score_round(Throw::Rock, Throw::Rock), 4);
-        assert_eq!(score_round(Throw::Rock, Throw::Paper), 1);
-        assert_eq!(score_round(Throw::Rock, Throw::Scissors), 7);
-        assert_eq!(score_round(Throw::Paper, Throw::Rock), 8);
-        assert_eq!(score_round(Throw::Paper, Throw::Paper), 5);
-        assert_eq!(score_round(Throw::Paper, Throw::Scissors), 2);
-        assert_eq!(score_round(Throw::Scissors, Throw::Rock), 3);
-        assert_eq!(score_round(Throw::Scissors, Throw::Paper), 9);
-        assert_eq!(score_round(Throw::Scissors, Throw::Scissors), 6);
+        let rules = Rules::default();
+        let scoring = Scoring::new(&rules);
+        let t = |name| rules.by_name(name).unwrap();
+        assert_eq!(score_round(&scoring, t("Rock"), t("Rock")), 4);
+        assert_eq!(score_round(&scoring, t("Rock"), t("Paper")), 1);
+        assert_eq!(score_round(&scoring, t("Rock"), t("Scissors")), 7);
+        assert_eq!(score_round(&scoring, t("Paper"), t("Rock")), 8);
+        assert_eq!(score_round(&scoring, t("Paper"), t("Paper")), 5);
+        assert_eq!(score_round(&scoring, t("Paper"), t("Scissors")), 2);
+        assert_eq!(score_round(&scoring, t("Scissors"), t("Rock")), 3);
+        assert_eq!(score_round(&scoring, t("Scissors"), t("Paper")), 9);
+        assert_eq!(score_round(&scoring, t("Scissors"), t("Scissors")), 6);
+    }
+
+    #[test]
+    fn test_parse_round() {
+        let rules = Rules::default();
+        let t = |name| rules.by_name(name).unwrap();
+        assert_eq!(
+            parse_round_as_throws(&rules, 1, "A Y").unwrap(),
+            vec![t("Rock"), t("Paper")]
+        );
+        assert_eq!(
+            parse_round_as_result(&rules, 1, "A Y").unwrap(),
+            vec![t("Rock"), t("Rock")]
+        );
+        assert_eq!(
+            parse_round_as_result(&rules, 1, "C Z").unwrap(),
+            vec![t("Scissors"), t("Rock")]
+        );
+    }
+
+    #[test]
+    fn test_parse_round_errors() {
+        let rules = Rules::default();
+        assert!(matches!(
+            parse_round_as_throws(&rules, 3, "A"),
+            Err(Error::MissingToken { line: 3, token: 2 })
+        ));
+        assert!(matches!(
+            parse_round_as_throws(&rules, 4, ""),
+            Err(Error::MissingToken { line: 4, token: 1 })
+        ));
+        assert!(matches!(
+            parse_round_as_throws(&rules, 1, "A Y Z"),
+            Err(Error::ExtraToken { line: 1, token: 3 })
+        ));
+        assert!(matches!(
+            parse_round_as_throws(&rules, 2, "D Y"),
+            Err(Error::UnknownThrow {
+                line: 2,
+                token: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_round_as_throws(&rules, 2, "A Q"),
+            Err(Error::UnknownThrow {
+                line: 2,
+                token: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_round_as_result(&rules, 5, "A Q"),
+            Err(Error::UnknownOutcome {
+                line: 5,
+                token: 2,
+                ..
+            })
+        ));
     }
 }
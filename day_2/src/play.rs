@@ -0,0 +1,132 @@
+use std::io::{self, BufRead, Write};
+
+use crate::rules::{Rules, Throw};
+use crate::score_round;
+use crate::scoring::Scoring;
+use crate::strategy::{Rng, Strategy};
+
+/// plays rounds until `q` or the end of `input`, the computer commits to
+/// its throw before reading yours so it can't cheat
+///
+/// returns every round as (computer, player) throws
+pub fn play(
+    rules: &Rules,
+    scoring: &Scoring,
+    computer: &mut dyn Strategy,
+    rng: &mut Rng,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<Vec<(Throw, Throw)>> {
+    let mut rounds = Vec::new();
+    let mut total = 0;
+    let shapes: Vec<&str> = (0..rules.len())
+        .map(|s| rules.name(rules.throw(s)))
+        .collect();
+    let mut line = String::new();
+
+    loop {
+        let theirs = computer.next_throw(rules, rng);
+        let yours = loop {
+            write!(output, "Your throw ({}, q to quit): ", shapes.join("/"))?;
+            output.flush()?;
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                writeln!(output)?;
+                return Ok(rounds);
+            }
+            let l = line.trim();
+            if l.eq_ignore_ascii_case("q") {
+                return Ok(rounds);
+            }
+            // shape names or the guide's codes both work
+            match rules.by_name(l).or_else(|| rules.player_throw(l).ok()) {
+                Some(t) => break t,
+                None => writeln!(output, "Unknown throw {l:?}")?,
+            }
+        };
+
+        let points = score_round(scoring, yours, theirs);
+        total += points;
+        let result = if yours > theirs {
+            "you win"
+        } else if yours < theirs {
+            "you lose"
+        } else {
+            "it's a draw"
+        };
+        writeln!(
+            output,
+            "Computer threw {}, {result}: {points} points ({total} total)",
+            rules.name(theirs)
+        )?;
+
+        computer.observe(theirs, yours);
+        rounds.push((theirs, yours));
+    }
+}
+
+/// rounds in the same "A X" format as the strategy guide
+pub fn transcript(rules: &Rules, rounds: &[(Throw, Throw)]) -> String {
+    rounds
+        .iter()
+        .map(|&(theirs, yours)| {
+            format!(
+                "{} {}\n",
+                rules.opponent_code(theirs),
+                rules.player_code(yours)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::play::{play, transcript};
+    use crate::rules::Rules;
+    use crate::scoring::Scoring;
+    use crate::strategy::{Guide, Rng};
+    use crate::{parse_round_as_throws, score_round};
+
+    #[test]
+    fn test_play() {
+        let rules = Rules::default();
+        let scoring = Scoring::new(&rules);
+        let mut rng = Rng::new(1);
+        let mut computer = Guide::new(vec![rules.throw(0), rules.throw(2)]);
+        let mut output = Vec::new();
+        let rounds = play(
+            &rules,
+            &scoring,
+            &mut computer,
+            &mut rng,
+            "paper\nlizard\nZ\nq\nrock\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rounds,
+            vec![
+                (rules.throw(0), rules.throw(1)),
+                (rules.throw(2), rules.throw(2))
+            ]
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Unknown throw \"lizard\""));
+        assert!(output.contains("Computer threw Rock, you win: 8 points (8 total)"));
+        assert!(output.contains("Computer threw Scissors, it's a draw: 6 points (14 total)"));
+
+        // and the transcript replays to the same score
+        let t = transcript(&rules, &rounds);
+        assert_eq!(t, "A Y\nC Z\n");
+        let replayed: i32 = t
+            .lines()
+            .enumerate()
+            .map(|(line, r)| {
+                let throws = parse_round_as_throws(&rules, line + 1, r).unwrap();
+                score_round(&scoring, throws[1], throws[0])
+            })
+            .sum();
+        assert_eq!(replayed, 14);
+    }
+}
@@ -0,0 +1,284 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::Error;
+
+/// plain old Rock-Paper-Scissors, used when no rules file is given
+pub const DEFAULT_RULES: &str = "\
+shape Rock A X
+shape Paper B Y
+shape Scissors C Z
+outcome lose X
+outcome draw Y
+outcome win Z
+";
+
+/// a shape from the rules, shapes are compared by their position in the
+/// cycle: each one beats the (n - 1) / 2 shapes listed right before it
+/// (wrapping around), and loses to the rest
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Throw {
+    shape: usize,
+    of: usize,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Lose => write!(f, "lose"),
+            Outcome::Draw => write!(f, "draw"),
+            Outcome::Win => write!(f, "win"),
+        }
+    }
+}
+
+impl Throw {
+    /// position of the shape in the rules
+    pub fn index(&self) -> usize {
+        self.shape
+    }
+
+    /// how a round goes for us throwing this against `opp`
+    pub fn outcome_against(&self, opp: Throw) -> Outcome {
+        if *self > opp {
+            Outcome::Win
+        } else if *self < opp {
+            Outcome::Lose
+        } else {
+            Outcome::Draw
+        }
+    }
+}
+
+impl PartialOrd for Throw {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        // throws from different games can't be compared
+        if self.of != other.of {
+            return None;
+        }
+        let diff = (self.shape + self.of - other.shape) % self.of;
+        if diff == 0 {
+            Some(Ordering::Equal)
+        } else if diff <= self.of / 2 {
+            Some(Ordering::Greater)
+        } else {
+            Some(Ordering::Less)
+        }
+    }
+}
+
+struct Shape {
+    name: String,
+    opp_code: String,
+    you_code: String,
+}
+
+/// the shapes of a game plus the codes the strategy guide uses for them
+pub struct Rules {
+    shapes: Vec<Shape>,
+    /// codes for lose, draw and win (in that order)
+    outcomes: [String; 3],
+}
+
+impl Rules {
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn throw(&self, shape: usize) -> Throw {
+        Throw {
+            shape,
+            of: self.shapes.len(),
+        }
+    }
+
+    pub fn name(&self, t: Throw) -> &str {
+        &self.shapes[t.shape].name
+    }
+
+    /// looks up a shape by name (case insensitive)
+    pub fn by_name(&self, name: &str) -> Option<Throw> {
+        self.shapes
+            .iter()
+            .position(|s| s.name.eq_ignore_ascii_case(name))
+            .map(|s| self.throw(s))
+    }
+
+    /// first column of the guide
+    pub fn opponent_throw(&self, code: &str) -> Result<Throw, Error> {
+        self.shapes
+            .iter()
+            .position(|s| s.opp_code == code)
+            .map(|s| self.throw(s))
+            .ok_or(Error::InvalidInput)
+    }
+
+    /// second column of the guide, read as the throw to make
+    pub fn player_throw(&self, code: &str) -> Result<Throw, Error> {
+        self.shapes
+            .iter()
+            .position(|s| s.you_code == code)
+            .map(|s| self.throw(s))
+            .ok_or(Error::InvalidInput)
+    }
+
+    /// second column of the guide, read as how the round should end
+    pub fn outcome(&self, code: &str) -> Result<Outcome, Error> {
+        match self.outcomes.iter().position(|o| o == code) {
+            Some(0) => Ok(Outcome::Lose),
+            Some(1) => Ok(Outcome::Draw),
+            Some(2) => Ok(Outcome::Win),
+            _ => Err(Error::InvalidInput),
+        }
+    }
+
+    pub fn opponent_code(&self, t: Throw) -> &str {
+        &self.shapes[t.shape].opp_code
+    }
+
+    pub fn player_code(&self, t: Throw) -> &str {
+        &self.shapes[t.shape].you_code
+    }
+
+    /// with more than three shapes there's several ways to win or lose,
+    /// we go with the neighbouring shape in the cycle
+    pub fn throw_for(&self, opp: Throw, outcome: Outcome) -> Throw {
+        let n = self.shapes.len();
+        match outcome {
+            Outcome::Lose => self.throw((opp.shape + n - 1) % n),
+            Outcome::Draw => opp,
+            Outcome::Win => self.throw((opp.shape + 1) % n),
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        DEFAULT_RULES.parse().unwrap()
+    }
+}
+
+/// one directive per line, blank lines and `#` comments are skipped:
+///
+///   shape <name> <opponent code> <player code>
+///   outcome <lose|draw|win> <code>
+///
+/// shapes are listed in cycle order
+impl FromStr for Rules {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut shapes: Vec<Shape> = Vec::new();
+        let mut outcomes: [Option<String>; 3] = Default::default();
+        let invalid = |line: usize, reason: &str| Error::InvalidRules {
+            line: line + 1,
+            reason: reason.to_string(),
+        };
+
+        for (line, l) in s.lines().enumerate() {
+            let l = l.split('#').next().unwrap_or_default();
+            let tokens: Vec<&str> = l.split_whitespace().collect();
+            match tokens.as_slice() {
+                [] => continue,
+                ["shape", name, opp_code, you_code] => {
+                    if shapes.iter().any(|s| s.name.eq_ignore_ascii_case(name)) {
+                        return Err(invalid(line, "duplicate shape name"));
+                    }
+                    if shapes.iter().any(|s| s.opp_code == *opp_code) {
+                        return Err(invalid(line, "duplicate opponent code"));
+                    }
+                    if shapes.iter().any(|s| s.you_code == *you_code) {
+                        return Err(invalid(line, "duplicate player code"));
+                    }
+                    shapes.push(Shape {
+                        name: name.to_string(),
+                        opp_code: opp_code.to_string(),
+                        you_code: you_code.to_string(),
+                    });
+                }
+                ["outcome", outcome, code] => {
+                    let idx = match *outcome {
+                        "lose" => 0,
+                        "draw" => 1,
+                        "win" => 2,
+                        _ => return Err(invalid(line, "outcome must be lose, draw or win")),
+                    };
+                    if outcomes.iter().flatten().any(|o| o == code) {
+                        return Err(invalid(line, "duplicate outcome code"));
+                    }
+                    outcomes[idx] = Some(code.to_string());
+                }
+                _ => return Err(invalid(line, "expected `shape` or `outcome` directive")),
+            }
+        }
+
+        if shapes.len() < 3 || shapes.len().is_multiple_of(2) {
+            return Err(Error::ShapeCount(shapes.len()));
+        }
+        match outcomes {
+            [Some(lose), Some(draw), Some(win)] => Ok(Rules {
+                shapes,
+                outcomes: [lose, draw, win],
+            }),
+            _ => Err(Error::MissingOutcome),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rules::{Outcome, Rules};
+    use crate::Error;
+
+    const RPSLS: &str = include_str!("../rpsls.txt");
+
+    #[test]
+    fn test_cycle_ordering() {
+        let rules: Rules = RPSLS.parse().unwrap();
+        let t = |name| rules.by_name(name).unwrap();
+        assert!(t("Rock") > t("Scissors"));
+        assert!(t("Rock") > t("Lizard"));
+        assert!(t("Paper") > t("Rock"));
+        assert!(t("Paper") > t("Spock"));
+        assert!(t("Scissors") > t("Paper"));
+        assert!(t("Scissors") > t("Lizard"));
+        assert!(t("Lizard") > t("Spock"));
+        assert!(t("Lizard") > t("Paper"));
+        assert!(t("Spock") > t("Scissors"));
+        assert!(t("Spock") > t("Rock"));
+        assert!(t("Rock") < t("Paper"));
+        assert!(t("Spock") == t("Spock"));
+    }
+
+    #[test]
+    fn test_throw_for() {
+        let rules = Rules::default();
+        for opp in (0..3).map(|s| rules.throw(s)) {
+            assert!(rules.throw_for(opp, Outcome::Win) > opp);
+            assert!(rules.throw_for(opp, Outcome::Lose) < opp);
+            assert!(rules.throw_for(opp, Outcome::Draw) == opp);
+        }
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let even = "shape A a x\nshape B b y\noutcome lose x\noutcome draw y\noutcome win z\n";
+        assert!(matches!(even.parse::<Rules>(), Err(Error::ShapeCount(2))));
+        assert!(matches!(
+            "shape A a x\nshape A b y\n".parse::<Rules>(),
+            Err(Error::InvalidRules { line: 2, .. })
+        ));
+        assert!(matches!(
+            "shape A a x\nshape B b y\nshape C c z\n".parse::<Rules>(),
+            Err(Error::MissingOutcome)
+        ));
+    }
+}
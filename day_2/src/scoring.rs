@@ -0,0 +1,115 @@
+use crate::rules::{Outcome, Rules, Throw};
+use crate::Error;
+
+/// points handed out for each round, the default matches the puzzle:
+/// 6/3/0 for a win/draw/loss plus the shape's position in the rules
+#[derive(Debug, PartialEq)]
+pub struct Scoring {
+    pub win: i32,
+    pub draw: i32,
+    pub lose: i32,
+    /// indexed by the shape's position in the rules
+    values: Vec<i32>,
+}
+
+impl Scoring {
+    pub fn new(rules: &Rules) -> Self {
+        Scoring {
+            win: 6,
+            draw: 3,
+            lose: 0,
+            values: (1..=rules.len() as i32).collect(),
+        }
+    }
+
+    /// reads a scoring table on top of the defaults, one directive per
+    /// line, blank lines and `#` comments are skipped:
+    ///
+    ///   points <lose|draw|win> <points>
+    ///   value <shape name> <points>
+    pub fn parse(s: &str, rules: &Rules) -> Result<Self, Error> {
+        let mut scoring = Scoring::new(rules);
+        let invalid = |line: usize, reason: &str| Error::InvalidScoring {
+            line: line + 1,
+            reason: reason.to_string(),
+        };
+
+        for (line, l) in s.lines().enumerate() {
+            let l = l.split('#').next().unwrap_or_default();
+            let tokens: Vec<&str> = l.split_whitespace().collect();
+            match tokens.as_slice() {
+                [] => continue,
+                ["points", outcome, points] => {
+                    let points = points
+                        .parse()
+                        .map_err(|_| invalid(line, "points must be a whole number"))?;
+                    match *outcome {
+                        "lose" => scoring.lose = points,
+                        "draw" => scoring.draw = points,
+                        "win" => scoring.win = points,
+                        _ => return Err(invalid(line, "outcome must be lose, draw or win")),
+                    }
+                }
+                ["value", name, points] => {
+                    let points = points
+                        .parse()
+                        .map_err(|_| invalid(line, "value must be a whole number"))?;
+                    scoring.set_value(rules, name, points)?;
+                }
+                _ => return Err(invalid(line, "expected `points` or `value` directive")),
+            }
+        }
+        Ok(scoring)
+    }
+
+    pub fn set_value(&mut self, rules: &Rules, name: &str, points: i32) -> Result<(), Error> {
+        let t = rules
+            .by_name(name)
+            .ok_or_else(|| Error::UnknownShape(name.to_string()))?;
+        self.values[t.index()] = points;
+        Ok(())
+    }
+
+    pub fn value(&self, t: Throw) -> i32 {
+        self.values[t.index()]
+    }
+
+    pub fn outcome_points(&self, outcome: Outcome) -> i32 {
+        match outcome {
+            Outcome::Lose => self.lose,
+            Outcome::Draw => self.draw,
+            Outcome::Win => self.win,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rules::{Outcome, Rules};
+    use crate::scoring::Scoring;
+    use crate::Error;
+
+    #[test]
+    fn test_parse_scoring() {
+        let rules = Rules::default();
+        let scoring = Scoring::parse(
+            "# tournament rules\npoints win 3\npoints draw 1\nvalue paper 10\n",
+            &rules,
+        )
+        .unwrap();
+        assert_eq!(scoring.outcome_points(Outcome::Win), 3);
+        assert_eq!(scoring.outcome_points(Outcome::Draw), 1);
+        assert_eq!(scoring.outcome_points(Outcome::Lose), 0);
+        assert_eq!(scoring.value(rules.throw(0)), 1);
+        assert_eq!(scoring.value(rules.throw(1)), 10);
+
+        assert!(matches!(
+            Scoring::parse("value Spock 5", &rules),
+            Err(Error::UnknownShape(_))
+        ));
+        assert!(matches!(
+            Scoring::parse("points win lots", &rules),
+            Err(Error::InvalidScoring { line: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt::{self, Display};
+
+use crate::rules::Rules;
+use crate::score_round;
+use crate::scoring::Scoring;
+use crate::strategy::{Rng, Strategy};
+
+/// rounds won, drawn and lost by one side of a matchup
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Tally {
+    pub won: usize,
+    pub drawn: usize,
+    pub lost: usize,
+}
+
+/// one strategy's line in the league table, a match is won by whoever
+/// scores more over all of its rounds
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub won: usize,
+    pub drawn: usize,
+    pub lost: usize,
+    /// 3 for a match win, 1 for a draw
+    pub points: usize,
+    pub score: i64,
+}
+
+pub struct Tournament {
+    /// in the order the strategies were entered
+    pub names: Vec<String>,
+    /// rounds[i][j] is how strategy i did against strategy j
+    pub rounds: Vec<Vec<Tally>>,
+    /// best first
+    pub table: Vec<Standing>,
+}
+
+/// round robin, every strategy plays every other one for `rounds` rounds
+pub fn run_tournament(
+    rules: &Rules,
+    scoring: &Scoring,
+    strategies: &mut [Box<dyn Strategy>],
+    rounds: usize,
+    rng: &mut Rng,
+) -> Tournament {
+    let n = strategies.len();
+    let names: Vec<String> = strategies.iter().map(|s| s.name().to_string()).collect();
+    let mut tallies = vec![vec![Tally::default(); n]; n];
+    let mut table: Vec<Standing> = names
+        .iter()
+        .map(|name| Standing {
+            name: name.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    for i in 0..n {
+        for j in i + 1..n {
+            let (left, right) = strategies.split_at_mut(j);
+            let (a, b) = (&mut left[i], &mut right[0]);
+            a.reset();
+            b.reset();
+
+            let (mut score_a, mut score_b) = (0, 0);
+            for _ in 0..rounds {
+                let ta = a.next_throw(rules, rng);
+                let tb = b.next_throw(rules, rng);
+                score_a += score_round(scoring, ta, tb) as i64;
+                score_b += score_round(scoring, tb, ta) as i64;
+                if ta > tb {
+                    tallies[i][j].won += 1;
+                    tallies[j][i].lost += 1;
+                } else if ta < tb {
+                    tallies[i][j].lost += 1;
+                    tallies[j][i].won += 1;
+                } else {
+                    tallies[i][j].drawn += 1;
+                    tallies[j][i].drawn += 1;
+                }
+                a.observe(ta, tb);
+                b.observe(tb, ta);
+            }
+
+            table[i].score += score_a;
+            table[j].score += score_b;
+            if score_a > score_b {
+                table[i].won += 1;
+                table[i].points += 3;
+                table[j].lost += 1;
+            } else if score_a < score_b {
+                table[j].won += 1;
+                table[j].points += 3;
+                table[i].lost += 1;
+            } else {
+                table[i].drawn += 1;
+                table[i].points += 1;
+                table[j].drawn += 1;
+                table[j].points += 1;
+            }
+        }
+    }
+
+    table.sort_by(|a, b| b.points.cmp(&a.points).then(b.score.cmp(&a.score)));
+    Tournament {
+        names,
+        rounds: tallies,
+        table,
+    }
+}
+
+impl Display for Tournament {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let w = self.names.iter().map(|n| n.len()).max().unwrap_or(0).max(8);
+        writeln!(
+            f,
+            "{:<w$} {:>3} {:>3} {:>3} {:>4} {:>10}",
+            "Strategy", "W", "D", "L", "Pts", "Score"
+        )?;
+        for s in &self.table {
+            writeln!(
+                f,
+                "{:<w$} {:>3} {:>3} {:>3} {:>4} {:>10}",
+                s.name, s.won, s.drawn, s.lost, s.points, s.score
+            )?;
+        }
+
+        // rounds won-drawn-lost, row against column
+        let cells: Vec<Vec<String>> = self
+            .rounds
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(j, t)| {
+                        if i == j {
+                            "-".to_string()
+                        } else {
+                            format!("{}/{}/{}", t.won, t.drawn, t.lost)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let cw = cells
+            .iter()
+            .flatten()
+            .map(|c| c.len())
+            .chain(self.names.iter().map(|n| n.len()))
+            .max()
+            .unwrap_or(0);
+        writeln!(f)?;
+        write!(f, "{:<w$}", "W/D/L")?;
+        for name in &self.names {
+            write!(f, " {name:>cw$}")?;
+        }
+        writeln!(f)?;
+        for (name, row) in self.names.iter().zip(&cells) {
+            write!(f, "{name:<w$}")?;
+            for c in row {
+                write!(f, " {c:>cw$}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rules::Rules;
+    use crate::scoring::Scoring;
+    use crate::simulate::run_tournament;
+    use crate::strategy::{Frequency, Guide, Rng, Strategy, Uniform};
+
+    #[test]
+    fn test_run_tournament() {
+        let rules = Rules::default();
+        let scoring = Scoring::new(&rules);
+        let mut rng = Rng::new(7);
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Uniform),
+            Box::new(Frequency::default()),
+            // always rock, frequency should pick that up straight away
+            Box::new(Guide::new(vec![rules.throw(0)])),
+        ];
+        let t = run_tournament(&rules, &scoring, &mut strategies, 100, &mut rng);
+
+        assert_eq!(t.names, vec!["uniform", "frequency", "guide"]);
+        for i in 0..3 {
+            for j in 0..3 {
+                let (a, b) = (t.rounds[i][j], t.rounds[j][i]);
+                assert_eq!((a.won, a.drawn, a.lost), (b.lost, b.drawn, b.won));
+                if i != j {
+                    assert_eq!(a.won + a.drawn + a.lost, 100);
+                }
+            }
+        }
+        // frequency loses at most the first (random) round to the rock bot
+        assert!(t.rounds[1][2].won >= 99);
+        assert_eq!(
+            t.table
+                .iter()
+                .map(|s| s.won + s.drawn + s.lost)
+                .sum::<usize>(),
+            6
+        );
+    }
+}
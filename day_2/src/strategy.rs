@@ -0,0 +1,230 @@
+use clap::ValueEnum;
+
+use crate::rules::{Outcome, Rules, Throw};
+
+/// the strategies that don't need a guide to play
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Uniform,
+    Frequency,
+    Markov,
+}
+
+impl Kind {
+    pub fn build(self) -> Box<dyn Strategy> {
+        match self {
+            Kind::Uniform => Box::new(Uniform),
+            Kind::Frequency => Box::new(Frequency::default()),
+            Kind::Markov => Box::new(Markov::default()),
+        }
+    }
+}
+
+/// small xorshift generator so runs can be repeated from a seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// roughly uniform in 0..n, the modulo bias doesn't matter at our sizes
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn throw(&mut self, rules: &Rules) -> Throw {
+        rules.throw(self.below(rules.len()))
+    }
+}
+
+/// something that picks throws, learning from what the other side plays
+pub trait Strategy {
+    fn name(&self) -> &str;
+
+    fn next_throw(&mut self, rules: &Rules, rng: &mut Rng) -> Throw;
+
+    /// called after every round with both throws
+    fn observe(&mut self, _mine: Throw, _theirs: Throw) {}
+
+    /// forget everything learnt, ready for a new opponent
+    fn reset(&mut self) {}
+}
+
+/// every shape is equally likely
+pub struct Uniform;
+
+impl Strategy for Uniform {
+    fn name(&self) -> &str {
+        "uniform"
+    }
+
+    fn next_throw(&mut self, rules: &Rules, rng: &mut Rng) -> Throw {
+        rng.throw(rules)
+    }
+}
+
+/// beats whatever the opponent has thrown most often so far
+#[derive(Default)]
+pub struct Frequency {
+    counts: Vec<usize>,
+}
+
+impl Strategy for Frequency {
+    fn name(&self) -> &str {
+        "frequency"
+    }
+
+    fn next_throw(&mut self, rules: &Rules, rng: &mut Rng) -> Throw {
+        let most = self.counts.iter().copied().max().unwrap_or(0);
+        if most == 0 {
+            return rng.throw(rules);
+        }
+        // ties go to whichever shape comes first
+        let likely = self.counts.iter().position(|&c| c == most).unwrap();
+        rules.throw_for(rules.throw(likely), Outcome::Win)
+    }
+
+    fn observe(&mut self, _mine: Throw, theirs: Throw) {
+        if self.counts.len() <= theirs.index() {
+            self.counts.resize(theirs.index() + 1, 0);
+        }
+        self.counts[theirs.index()] += 1;
+    }
+
+    fn reset(&mut self) {
+        self.counts.clear();
+    }
+}
+
+/// first order Markov chain over the opponent's throws, predicts their next
+/// throw from their last one and beats it
+#[derive(Default)]
+pub struct Markov {
+    /// transitions[from][to] counts
+    transitions: Vec<Vec<usize>>,
+    last: Option<usize>,
+}
+
+impl Strategy for Markov {
+    fn name(&self) -> &str {
+        "markov"
+    }
+
+    fn next_throw(&mut self, rules: &Rules, rng: &mut Rng) -> Throw {
+        let row = match self.last.and_then(|l| self.transitions.get(l)) {
+            Some(row) => row,
+            None => return rng.throw(rules),
+        };
+        let most = row.iter().copied().max().unwrap_or(0);
+        if most == 0 {
+            return rng.throw(rules);
+        }
+        let likely = row.iter().position(|&c| c == most).unwrap();
+        rules.throw_for(rules.throw(likely), Outcome::Win)
+    }
+
+    fn observe(&mut self, _mine: Throw, theirs: Throw) {
+        let to = theirs.index();
+        if let Some(from) = self.last {
+            let n = (from.max(to) + 1).max(self.transitions.len());
+            self.transitions.resize(n, Vec::new());
+            for row in self.transitions.iter_mut() {
+                row.resize(n, 0);
+            }
+            self.transitions[from][to] += 1;
+        }
+        self.last = Some(to);
+    }
+
+    fn reset(&mut self) {
+        self.transitions.clear();
+        self.last = None;
+    }
+}
+
+/// plays the second column of the strategy guide in order, over and over
+pub struct Guide {
+    throws: Vec<Throw>,
+    next: usize,
+}
+
+impl Guide {
+    pub fn new(throws: Vec<Throw>) -> Self {
+        Guide { throws, next: 0 }
+    }
+}
+
+impl Strategy for Guide {
+    fn name(&self) -> &str {
+        "guide"
+    }
+
+    fn next_throw(&mut self, rules: &Rules, rng: &mut Rng) -> Throw {
+        if self.throws.is_empty() {
+            return rng.throw(rules);
+        }
+        let t = self.throws[self.next % self.throws.len()];
+        self.next += 1;
+        t
+    }
+
+    fn reset(&mut self) {
+        self.next = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rules::Rules;
+    use crate::strategy::{Frequency, Guide, Markov, Rng, Strategy};
+
+    #[test]
+    fn test_rng_below() {
+        let mut rng = Rng::new(0);
+        assert!((0..1000).all(|_| rng.below(3) < 3));
+    }
+
+    #[test]
+    fn test_frequency() {
+        let rules = Rules::default();
+        let mut rng = Rng::new(1);
+        let mut s = Frequency::default();
+        for t in [0, 1, 1, 2] {
+            s.observe(rules.throw(0), rules.throw(t));
+        }
+        // they like paper, so we go scissors
+        assert_eq!(s.next_throw(&rules, &mut rng), rules.throw(2));
+    }
+
+    #[test]
+    fn test_markov() {
+        let rules = Rules::default();
+        let mut rng = Rng::new(1);
+        let mut s = Markov::default();
+        // rock is always followed by scissors
+        for t in [0, 2, 1, 0, 2, 0] {
+            s.observe(rules.throw(0), rules.throw(t));
+        }
+        assert_eq!(s.next_throw(&rules, &mut rng), rules.throw(0));
+    }
+
+    #[test]
+    fn test_guide() {
+        let rules = Rules::default();
+        let mut rng = Rng::new(1);
+        let mut s = Guide::new(vec![rules.throw(1), rules.throw(2)]);
+        let played: Vec<_> = (0..3).map(|_| s.next_throw(&rules, &mut rng)).collect();
+        assert_eq!(played, vec![rules.throw(1), rules.throw(2), rules.throw(1)]);
+        s.reset();
+        assert_eq!(s.next_throw(&rules, &mut rng), rules.throw(1));
+    }
+}
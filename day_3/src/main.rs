@@ -1,9 +1,19 @@
 use std::{
     collections::{HashMap, HashSet},
+    fmt::{self, Display},
     fs,
+    hint::black_box,
+    process,
+    time::Instant,
 };
 
-use clap::Parser;
+use clap::{builder::RangedU64ValueParser, Parser, ValueEnum};
+use thiserror::Error;
+
+mod regroup;
+mod repack;
+use regroup::{badge, regroup, Regrouping};
+use repack::repack;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -11,21 +21,329 @@ use clap::Parser;
     version = "v1.0.0",
     about = "Advent of Code (Day 3)"
 )]
-
 struct Cli {
     #[clap(short, long)]
     input: String,
+    /// sacks per badge group
+    #[clap(short, long, default_value_t = 3, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    group_size: usize,
+    /// what to do with a last group that's short of --group-size sacks
+    #[clap(short, long, value_enum, default_value_t = Remainder::Error)]
+    remainder: Remainder,
+    /// list every group's badge
+    #[clap(short, long)]
+    badges: bool,
+    /// the sacks are in no particular order, work out the groups first
+    #[clap(short, long)]
+    shuffled: bool,
+    /// plan the fewest swaps per sack so no item is in both compartments,
+    /// instead of working out the answers
+    #[clap(short = 'p', long)]
+    repack: bool,
+    /// time the hash set and bitmask solvers over this many runs
+    #[clap(long, value_name = "RUNS")]
+    bench: Option<u32>,
 }
 
 fn main() {
     let cli_args = Cli::parse();
 
     let input = &fs::read_to_string(cli_args.input).unwrap();
+    let sacks = match parse_sacks(input, !cli_args.repack) {
+        Ok(sacks) => sacks,
+        Err(errors) => fail(&errors),
+    };
+    if cli_args.repack {
+        print_repacking(&sacks);
+        return;
+    }
+    let part2 = if cli_args.shuffled {
+        solve_shuffled(
+            &sacks,
+            cli_args.group_size,
+            cli_args.remainder,
+            cli_args.badges,
+        )
+    } else {
+        let badges = match find_badges(sacks, cli_args.group_size, cli_args.remainder) {
+            Ok(badges) => badges,
+            Err(errors) => fail(&errors),
+        };
+        if cli_args.badges {
+            for b in &badges {
+                println!("{b}");
+            }
+        }
+        badges.iter().map(|b| b.priority()).sum()
+    };
+
     println!("Part 1: {}", solve_part1(input));
-    println!("Part 2: {}", solve_part2(input));
+    println!("Part 2: {part2}");
+
+    if let Some(runs) = cli_args.bench {
+        bench("Part 1 (sets)", runs, || solve_part1_sets(input));
+        bench("Part 1 (masks)", runs, || solve_part1(input));
+        let size = cli_args.group_size;
+        bench("Part 2 (sets)", runs, || solve_part2_sets(input, size));
+        bench("Part 2 (masks)", runs, || solve_part2(input, size));
+    }
+}
+
+fn bench(name: &str, runs: u32, f: impl Fn() -> i32) {
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(f());
+    }
+    let per_run = start.elapsed() / runs.max(1);
+    println!("{name:<15} {:>10.3} us/run", per_run.as_secs_f64() * 1e6);
+}
+
+/// regroups the sacks, then scores the badges like part 2 does
+fn solve_shuffled(sacks: &[&str], size: usize, remainder: Remainder, list: bool) -> i32 {
+    let left = sacks.len() % size;
+    if left != 0 && remainder == Remainder::Error {
+        fail(&[Error::PartialGroup { left, size }]);
+    }
+    let total = |groups: &[Vec<usize>]| -> i32 {
+        groups
+            .iter()
+            .map(|g| to_priority(&badge(sacks, g)).unwrap())
+            .sum()
+    };
+    let groups = match regroup(sacks, size, remainder) {
+        Regrouping::Impossible => fail(&[Error::NoPartition { size }]),
+        Regrouping::Unique(groups) => {
+            println!("Partition: unique");
+            groups
+        }
+        Regrouping::Ambiguous(groups, other) => {
+            println!("Partition: ambiguous, another one totals {}", total(&other));
+            groups
+        }
+    };
+
+    if list {
+        for (g, group) in groups.iter().enumerate() {
+            let lines: Vec<String> = group.iter().map(|s| (s + 1).to_string()).collect();
+            let item = badge(sacks, group);
+            println!(
+                "Group {} (lines {}): {item} ({})",
+                g + 1,
+                lines.join(", "),
+                to_priority(&item).unwrap()
+            );
+        }
+    }
+    total(&groups)
+}
+
+fn print_repacking(sacks: &[&str]) {
+    let (mut total, mut infeasible) = (0, 0);
+    for (line, sack) in sacks.iter().enumerate() {
+        match repack(sack) {
+            Some(swaps) if swaps.is_empty() => println!("Line {}: nothing to swap", line + 1),
+            Some(swaps) => {
+                let list: Vec<String> = swaps.iter().map(|s| s.to_string()).collect();
+                println!("Line {}: {}", line + 1, list.join(" "));
+                total += swaps.len();
+            }
+            None => {
+                println!("Line {}: infeasible", line + 1);
+                infeasible += 1;
+            }
+        }
+    }
+    println!("Total swaps: {total} ({infeasible} sacks infeasible)");
+}
+
+fn fail(errors: &[Error]) -> ! {
+    for e in errors {
+        eprintln!("{e}");
+    }
+    if errors.len() > 1 {
+        eprintln!("error: found {} problems", errors.len());
+    }
+    process::exit(1);
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("line {line}: odd number of items ({len}), can't split into compartments")]
+    OddLength { line: usize, len: usize },
+    #[error("line {line}, column {column}: {item:?} isn't an item")]
+    InvalidItem {
+        line: usize,
+        column: usize,
+        item: char,
+    },
+    #[error("line {line}: no item in both compartments")]
+    NoDuplicate { line: usize },
+    #[error("line {line}: more than one item in both compartments ({items})")]
+    SeveralDuplicates { line: usize, items: String },
+    #[error("{left} sacks left over after the last group of {size}, see --remainder")]
+    PartialGroup { left: usize, size: usize },
+    #[error("group {group} (lines {first}-{last}): no badge, nothing in every sack")]
+    NoBadge {
+        group: usize,
+        first: usize,
+        last: usize,
+    },
+    #[error("group {group} (lines {first}-{last}): more than one badge ({items})")]
+    SeveralBadges {
+        group: usize,
+        first: usize,
+        last: usize,
+        items: String,
+    },
+    #[error("no way to split the sacks into groups of {size} with one badge each")]
+    NoPartition { size: usize },
+}
+
+/// handling for the sacks after the last full group
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Remainder {
+    Error,
+    Ignore,
+    /// treat them as a group of their own
+    Process,
+}
+
+/// the one item carried by every elf in a group
+#[derive(Debug, PartialEq)]
+struct Badge {
+    /// numbered from 1, like the lines
+    group: usize,
+    first: usize,
+    last: usize,
+    item: char,
+}
+
+impl Badge {
+    fn priority(&self) -> i32 {
+        to_priority(&self.item).unwrap()
+    }
+}
+
+impl Display for Badge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Group {} (lines {}-{}): {} ({})",
+            self.group,
+            self.first,
+            self.last,
+            self.item,
+            self.priority()
+        )
+    }
+}
+
+/// splits the sacks into groups of `size` and finds each group's badge, every
+/// group has to share exactly one item
+fn find_badges(
+    sacks: Vec<&str>,
+    size: usize,
+    remainder: Remainder,
+) -> Result<Vec<Badge>, Vec<Error>> {
+    let left = sacks.len() % size;
+    let mut groups = group_items(sacks, size);
+    if left != 0 {
+        match remainder {
+            Remainder::Error => return Err(vec![Error::PartialGroup { left, size }]),
+            Remainder::Ignore => {
+                groups.pop();
+            }
+            Remainder::Process => {}
+        }
+    }
+
+    let mut badges = Vec::new();
+    let mut errors = Vec::new();
+    for (g, group) in groups.iter().enumerate() {
+        let first = g * size + 1;
+        let last = first + group.len() - 1;
+        let common = mask_common(group);
+        match common.count_ones() {
+            0 => errors.push(Error::NoBadge {
+                group: g + 1,
+                first,
+                last,
+            }),
+            1 => badges.push(Badge {
+                group: g + 1,
+                first,
+                last,
+                item: mask_to_items(common)[0],
+            }),
+            _ => errors.push(Error::SeveralBadges {
+                group: g + 1,
+                first,
+                last,
+                items: mask_to_items(common).into_iter().collect(),
+            }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(badges)
+    } else {
+        Err(errors)
+    }
 }
 
+/// checks every sack before anything gets scored, reporting all the
+/// problems rather than stopping at the first
+///
+/// scoring needs exactly one item in both compartments, with `shared` off
+/// only the items themselves are checked (repacking fixes the rest)
+fn parse_sacks(input: &str, shared: bool) -> Result<Vec<&str>, Vec<Error>> {
+    let mut errors = Vec::new();
+    for (line, sack) in input.split_terminator('\n').enumerate() {
+        let line = line + 1;
+        let mut valid = true;
+        for (column, item) in sack.chars().enumerate() {
+            if to_priority(&item).is_none() {
+                valid = false;
+                errors.push(Error::InvalidItem {
+                    line,
+                    column: column + 1,
+                    item,
+                });
+            }
+        }
+        // anything that isn't an item might be more than one byte wide
+        let len = sack.chars().count();
+        if len % 2 != 0 {
+            errors.push(Error::OddLength { line, len });
+            continue;
+        }
+        if !valid || !shared {
+            continue;
+        }
+
+        let duplicates = mask_duplicates(sep_compartments(sack));
+        match duplicates.count_ones() {
+            0 => errors.push(Error::NoDuplicate { line }),
+            1 => {}
+            _ => errors.push(Error::SeveralDuplicates {
+                line,
+                items: mask_to_items(duplicates).into_iter().collect(),
+            }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(input.split_terminator('\n').collect())
+    } else {
+        Err(errors)
+    }
+}
+
+/// None for anything that isn't an ASCII letter
 fn to_priority(item: &char) -> Option<i32> {
+    if !item.is_ascii_alphabetic() {
+        return None;
+    }
     let offset: i32 = if item.is_lowercase() { -9 } else { 17 };
     item.to_digit(36).map(|x| x as i32 + offset)
 }
@@ -50,23 +368,9 @@ fn return_duplicates(compartments: (&str, &str)) -> Vec<char> {
     out
 }
 
-/// panics if items is not evenly divisble by group_len
+/// the last group is short if items is not evenly divisble by group_len
 fn group_items(items: Vec<&str>, group_len: usize) -> Vec<Vec<&str>> {
-    let mut out = Vec::new();
-
-    let mut g = 0;
-    let groups = items.len() / group_len;
-    while g < groups {
-        out.push(vec![]);
-        g += 1;
-    }
-
-    for item in items.into_iter().enumerate() {
-        let group_num = item.0 / group_len;
-        out[group_num].push(item.1);
-    }
-
-    out
+    items.chunks(group_len).map(|g| g.to_vec()).collect()
 }
 
 fn find_common_elements(group: Vec<&str>) -> Vec<char> {
@@ -83,7 +387,65 @@ fn find_common_elements(group: Vec<&str>) -> Vec<char> {
         .collect::<Vec<char>>()
 }
 
+/// every item type in `items` as a bit, indexed by its priority, anything
+/// that isn't an item is left out
+fn to_mask(items: &str) -> u64 {
+    items
+        .chars()
+        .filter_map(|c| to_priority(&c))
+        .fold(0, |mask, p| mask | 1 << p)
+}
+
+/// bitmask version of `return_duplicates`
+fn mask_duplicates(compartments: (&str, &str)) -> u64 {
+    to_mask(compartments.0) & to_mask(compartments.1)
+}
+
+/// bitmask version of `find_common_elements`
+fn mask_common(group: &[&str]) -> u64 {
+    group
+        .iter()
+        .map(|g| to_mask(g))
+        .reduce(|a, m| a & m)
+        .unwrap_or(0)
+}
+
+/// the items in a mask, in the same (sorted) order `return_duplicates` uses
+fn mask_to_items(mask: u64) -> Vec<char> {
+    let upper = (27..=52).map(|p| (p, (b'A' + (p - 27)) as char));
+    let lower = (1..=26).map(|p| (p, (b'a' + (p - 1)) as char));
+    upper
+        .chain(lower)
+        .filter(|(p, _)| mask & 1 << p != 0)
+        .map(|(_, c)| c)
+        .collect()
+}
+
+/// sum of the priorities in a mask, which are just the set bit positions
+fn mask_priority(mask: u64) -> i32 {
+    (1..=52).filter(|p| mask & 1 << p != 0).sum()
+}
+
 fn solve_part1(input: &str) -> i32 {
+    input
+        .split_terminator('\n')
+        .map(sep_compartments)
+        .map(mask_duplicates)
+        .map(mask_priority)
+        .sum()
+}
+
+fn solve_part2(input: &str, group_len: usize) -> i32 {
+    group_items(input.split_terminator('\n').collect(), group_len)
+        .iter()
+        .map(|g| mask_common(g))
+        .map(mask_priority)
+        .sum()
+}
+
+/// the original hash map/set solvers (this and `solve_part2_sets`), only
+/// kept around for `--bench` to time the bitmask ones against
+fn solve_part1_sets(input: &str) -> i32 {
     input
         .split_terminator('\n')
         .map(sep_compartments)
@@ -92,8 +454,8 @@ fn solve_part1(input: &str) -> i32 {
         .sum()
 }
 
-fn solve_part2(input: &str) -> i32 {
-    group_items(input.split_terminator('\n').collect(), 3)
+fn solve_part2_sets(input: &str, group_len: usize) -> i32 {
+    group_items(input.split_terminator('\n').collect(), group_len)
         .into_iter()
         .map(find_common_elements)
         .map(|x| x.into_iter().map(|y| to_priority(&y).unwrap()).sum::<i32>())
@@ -103,7 +465,10 @@ fn solve_part2(input: &str) -> i32 {
 #[cfg(test)]
 mod test {
     use crate::{
-        find_common_elements, group_items, return_duplicates, sep_compartments, to_priority,
+        find_badges, find_common_elements, group_items, mask_common, mask_duplicates,
+        mask_priority, mask_to_items, parse_sacks, return_duplicates, sep_compartments,
+        solve_part1, solve_part1_sets, solve_part2, solve_part2_sets, to_mask, to_priority, Badge,
+        Error, Remainder,
     };
 
     #[test]
@@ -112,6 +477,8 @@ mod test {
         assert_eq!(to_priority(&'z').unwrap(), 26);
         assert_eq!(to_priority(&'A').unwrap(), 27);
         assert_eq!(to_priority(&'Z').unwrap(), 52);
+        assert_eq!(to_priority(&'1'), None);
+        assert_eq!(to_priority(&'é'), None);
     }
 
     #[test]
@@ -145,6 +512,10 @@ mod test {
             group_items(vec!["a", "b", "c", "d", "e", "f"], 3),
             vec![vec!["a", "b", "c"], vec!["d", "e", "f"]]
         );
+        assert_eq!(
+            group_items(vec!["a", "b", "c", "d"], 3),
+            vec![vec!["a", "b", "c"], vec!["d"]]
+        );
     }
 
     #[test]
@@ -166,4 +537,140 @@ mod test {
             vec!['Z']
         );
     }
+
+    #[test]
+    fn test_to_mask() {
+        assert_eq!(to_mask(""), 0);
+        assert_eq!(to_mask("aa"), 1 << 1);
+        assert_eq!(to_mask("aZ"), 1 << 1 | 1 << 52);
+        assert_eq!(mask_to_items(to_mask("zaZA")), vec!['A', 'Z', 'a', 'z']);
+        assert_eq!(mask_priority(to_mask("aZ")), 53);
+    }
+
+    #[test]
+    fn test_masks_match_sets() {
+        let input = include_str!("../test.txt");
+        for sack in input.lines() {
+            let c = sep_compartments(sack);
+            assert_eq!(mask_to_items(mask_duplicates(c)), return_duplicates(c));
+        }
+        for group in group_items(input.lines().collect(), 3) {
+            let mut common = find_common_elements(group.clone());
+            common.sort();
+            assert_eq!(mask_to_items(mask_common(&group)), common);
+        }
+        assert_eq!(solve_part1(input), solve_part1_sets(input));
+        assert_eq!(solve_part2(input, 3), solve_part2_sets(input, 3));
+        assert_eq!(solve_part1(input), 157);
+        assert_eq!(solve_part2(input, 3), 70);
+    }
+
+    #[test]
+    fn test_parse_sacks() {
+        assert_eq!(
+            parse_sacks(include_str!("../test.txt"), true)
+                .unwrap()
+                .len(),
+            6
+        );
+        let input = "abcA\nabca\nab1b\nabcab\nabab\nxyéx\n";
+        assert_eq!(
+            parse_sacks(input, true).unwrap_err(),
+            vec![
+                Error::NoDuplicate { line: 1 },
+                Error::InvalidItem {
+                    line: 3,
+                    column: 3,
+                    item: '1'
+                },
+                Error::OddLength { line: 4, len: 5 },
+                Error::SeveralDuplicates {
+                    line: 5,
+                    items: "ab".to_string()
+                },
+                Error::InvalidItem {
+                    line: 6,
+                    column: 3,
+                    item: 'é'
+                },
+            ]
+        );
+        // repacking only cares about the items
+        assert_eq!(
+            parse_sacks(input, false).unwrap_err(),
+            vec![
+                Error::InvalidItem {
+                    line: 3,
+                    column: 3,
+                    item: '1'
+                },
+                Error::OddLength { line: 4, len: 5 },
+                Error::InvalidItem {
+                    line: 6,
+                    column: 3,
+                    item: 'é'
+                },
+            ]
+        );
+        assert_eq!(parse_sacks("abcd\nabab\n", false).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_find_badges() {
+        let sacks: Vec<&str> = include_str!("../test.txt").lines().collect();
+        let badges = find_badges(sacks, 3, Remainder::Error).unwrap();
+        assert_eq!(
+            badges,
+            vec![
+                Badge {
+                    group: 1,
+                    first: 1,
+                    last: 3,
+                    item: 'r'
+                },
+                Badge {
+                    group: 2,
+                    first: 4,
+                    last: 6,
+                    item: 'Z'
+                },
+            ]
+        );
+        assert_eq!(badges[1].to_string(), "Group 2 (lines 4-6): Z (52)");
+
+        let sacks = vec!["ab", "bc", "bd", "cd"];
+        assert_eq!(
+            find_badges(sacks.clone(), 3, Remainder::Error).unwrap_err(),
+            vec![Error::PartialGroup { left: 1, size: 3 }]
+        );
+        assert_eq!(
+            find_badges(sacks.clone(), 3, Remainder::Ignore)
+                .unwrap()
+                .len(),
+            1
+        );
+        // a lone sack has all of its items in common
+        assert_eq!(
+            find_badges(sacks, 3, Remainder::Process).unwrap_err(),
+            vec![Error::SeveralBadges {
+                group: 2,
+                first: 4,
+                last: 4,
+                items: "cd".to_string()
+            }]
+        );
+        let badges = find_badges(vec!["ab", "bc", "bd", "d"], 3, Remainder::Process).unwrap();
+        assert_eq!(
+            (badges[1].first, badges[1].last, badges[1].item),
+            (4, 4, 'd')
+        );
+        assert_eq!(
+            find_badges(vec!["ab", "cd"], 2, Remainder::Error).unwrap_err(),
+            vec![Error::NoBadge {
+                group: 1,
+                first: 1,
+                last: 2
+            }]
+        );
+    }
 }
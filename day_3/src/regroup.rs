@@ -0,0 +1,263 @@
+use crate::{find_common_elements, to_mask, Remainder};
+
+/// what we could work out about which sacks go together
+#[derive(Debug, PartialEq)]
+pub enum Regrouping {
+    /// no way of splitting the sacks up
+    Impossible,
+    Unique(Vec<Vec<usize>>),
+    /// two different partitions, there may well be more
+    Ambiguous(Vec<Vec<usize>>, Vec<Vec<usize>>),
+}
+
+/// splits sacks that have been shuffled back into groups of `size`, where
+/// every group shares exactly one item, groups hold indexes into `sacks`
+///
+/// sacks that don't make up a full group are handled like `remainder` says,
+/// either any of them can be left out or they make a short group of their own
+pub fn regroup(sacks: &[&str], size: usize, remainder: Remainder) -> Regrouping {
+    let left = sacks.len() % size;
+    let spare = match remainder {
+        Remainder::Error if left != 0 => return Regrouping::Impossible,
+        Remainder::Ignore => left,
+        _ => 0,
+    };
+    let mut search = Search {
+        masks: sacks.iter().map(|s| to_mask(s)).collect(),
+        size,
+        spare,
+        used: vec![false; sacks.len()],
+        groups: Vec::new(),
+        found: Vec::new(),
+    };
+    if remainder == Remainder::Process && left != 0 {
+        search.short_group(left);
+    } else {
+        search.next_group();
+    }
+
+    let mut found = search.found.into_iter();
+    match (found.next(), found.next()) {
+        (None, _) => Regrouping::Impossible,
+        (Some(p), None) => Regrouping::Unique(p),
+        (Some(p), Some(q)) => Regrouping::Ambiguous(p, q),
+    }
+}
+
+/// the single item a group has in common
+pub fn badge(sacks: &[&str], group: &[usize]) -> char {
+    find_common_elements(group.iter().map(|&s| sacks[s]).collect())[0]
+}
+
+/// one way of dealing with a sack
+enum Pick {
+    Group(Vec<usize>),
+    /// left out of every group
+    Skip(usize),
+}
+
+struct Search {
+    masks: Vec<u64>,
+    size: usize,
+    /// sacks that can still be left out
+    spare: usize,
+    used: Vec<bool>,
+    groups: Vec<Vec<usize>>,
+    /// stops at two, that's enough to know it's ambiguous
+    found: Vec<Vec<Vec<usize>>>,
+}
+
+impl Search {
+    /// tries each short group of `len` sacks before the full groups, there
+    /// are a lot fewer of them than sacks that could end up in one
+    fn short_group(&mut self, len: usize) {
+        let mut options = Vec::new();
+        for s in 0..self.masks.len() {
+            self.fill(
+                &mut vec![s],
+                self.masks[s],
+                s + 1,
+                len,
+                usize::MAX,
+                &mut options,
+            );
+        }
+        for group in options {
+            self.with_group(group);
+            if self.found.len() >= 2 {
+                return;
+            }
+        }
+    }
+
+    /// groups the sack with the fewest ways of being grouped first, so dead
+    /// ends (a sack with no way at all) turn up as early as possible
+    ///
+    /// every partition puts that sack in exactly one group, so no partition
+    /// gets found twice
+    ///
+    /// a sack with no group left has to be left out, so it's a dead end too
+    /// once more of them turn up than can be spared
+    fn next_group(&mut self) {
+        let mut best: Option<Vec<Pick>> = None;
+        let mut stuck = 0;
+        for s in 0..self.masks.len() {
+            if self.used[s] {
+                continue;
+            }
+            let limit = best.as_ref().map_or(usize::MAX, |b| b.len());
+            let options = self.groups_with(s, limit);
+            if options.is_empty() {
+                return;
+            }
+            if let Pick::Skip(_) = options[0] {
+                stuck += 1;
+                if stuck > self.spare {
+                    return;
+                }
+            }
+            if options.len() < limit {
+                best = Some(options);
+            }
+        }
+
+        let best = match best {
+            Some(best) => best,
+            None => {
+                self.found.push(self.groups.clone());
+                return;
+            }
+        };
+        for pick in best {
+            match pick {
+                Pick::Group(group) => self.with_group(group),
+                Pick::Skip(s) => {
+                    self.used[s] = true;
+                    self.spare -= 1;
+                    self.next_group();
+                    self.spare += 1;
+                    self.used[s] = false;
+                }
+            }
+            if self.found.len() >= 2 {
+                return;
+            }
+        }
+    }
+
+    /// carries on searching with `group` added
+    fn with_group(&mut self, group: Vec<usize>) {
+        for &s in &group {
+            self.used[s] = true;
+        }
+        self.groups.push(group);
+        self.next_group();
+        let group = self.groups.pop().unwrap();
+        for &s in &group {
+            self.used[s] = false;
+        }
+    }
+
+    /// the ungrouped sacks `s` could make a group with, plus leaving it out
+    /// if that's still allowed, giving up at `limit`
+    fn groups_with(&self, s: usize, limit: usize) -> Vec<Pick> {
+        let mut out = Vec::new();
+        self.fill(&mut vec![s], self.masks[s], 0, self.size, limit, &mut out);
+        let mut picks: Vec<Pick> = out.into_iter().map(Pick::Group).collect();
+        if self.spare > 0 && picks.len() < limit {
+            picks.push(Pick::Skip(s));
+        }
+        picks
+    }
+
+    /// `common` is what everything in `group` shares so far, once it's empty
+    /// there's no point adding more sacks
+    fn fill(
+        &self,
+        group: &mut Vec<usize>,
+        common: u64,
+        from: usize,
+        size: usize,
+        limit: usize,
+        out: &mut Vec<Vec<usize>>,
+    ) {
+        if group.len() == size {
+            if common.count_ones() == 1 {
+                let mut g = group.clone();
+                g.sort_unstable();
+                out.push(g);
+            }
+            return;
+        }
+        for s in from..self.masks.len() {
+            if out.len() >= limit {
+                return;
+            }
+            let shared = common & self.masks[s];
+            if self.used[s] || s == group[0] || shared == 0 {
+                continue;
+            }
+            group.push(s);
+            self.fill(group, shared, s + 1, size, limit, out);
+            group.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::regroup::{badge, regroup, Regrouping};
+    use crate::Remainder;
+
+    #[test]
+    fn test_regroup() {
+        // the two pairs only work one way
+        let sacks = ["ab", "cd", "bx", "dy"];
+        assert_eq!(
+            regroup(&sacks, 2, Remainder::Error),
+            Regrouping::Unique(vec![vec![0, 2], vec![1, 3]])
+        );
+        assert_eq!(badge(&sacks, &[1, 3]), 'd');
+
+        // ab goes with bc or ad, and the leftovers pair up either way
+        let sacks = ["ab", "ac", "bc", "ad"];
+        assert!(matches!(
+            regroup(&sacks, 2, Remainder::Error),
+            Regrouping::Ambiguous(..)
+        ));
+
+        assert_eq!(
+            regroup(&["ab", "cd"], 2, Remainder::Error),
+            Regrouping::Impossible
+        );
+        assert_eq!(
+            regroup(&["ab", "ab", "ab"], 2, Remainder::Error),
+            Regrouping::Impossible
+        );
+    }
+
+    #[test]
+    fn test_regroup_remainder() {
+        // cd is the only sack that can be left out
+        assert_eq!(
+            regroup(&["ab", "cd", "bx"], 2, Remainder::Ignore),
+            Regrouping::Unique(vec![vec![0, 2]])
+        );
+        // any two of these pair up on b, so any of them can
+        assert!(matches!(
+            regroup(&["ab", "bc", "bd"], 2, Remainder::Ignore),
+            Regrouping::Ambiguous(..)
+        ));
+
+        // c makes a short group on its own
+        assert_eq!(
+            regroup(&["ab", "c", "bx"], 2, Remainder::Process),
+            Regrouping::Unique(vec![vec![1], vec![0, 2]])
+        );
+        // but cd has two items, neither of which is a badge
+        assert_eq!(
+            regroup(&["ab", "cd", "bx"], 2, Remainder::Process),
+            Regrouping::Impossible
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt::{self, Display};
+
+use crate::{sep_compartments, to_priority};
+
+/// trades the item at one column of the first compartment for the item at
+/// a column of the second, columns count from 1 like the lines do
+#[derive(Debug, PartialEq)]
+pub struct Swap {
+    pub left: usize,
+    pub right: usize,
+    pub items: (char, char),
+}
+
+/// the fewest swaps that leave every item type in just one compartment, or
+/// None if the types can't be split into two halves of the right size
+///
+/// each type ends up wholly on one side, so it's a subset sum over the types
+/// filling the first compartment exactly, costing whatever of them starts in
+/// the second
+pub fn repack(sack: &str) -> Option<Vec<Swap>> {
+    let (first, second) = sep_compartments(sack);
+    let half = first.len();
+    // (in first, in second) for each priority
+    let mut counts = [(0, 0); 53];
+    for c in first.chars() {
+        counts[to_priority(&c)? as usize].0 += 1;
+    }
+    for c in second.chars() {
+        counts[to_priority(&c)? as usize].1 += 1;
+    }
+    let types: Vec<usize> = (1..53).filter(|&t| counts[t] != (0, 0)).collect();
+
+    // best[i][w] is the fewest moves into the first compartment that fill
+    // w of it from the first i types
+    let mut best = vec![vec![None; half + 1]; types.len() + 1];
+    best[0][0] = Some(0);
+    for (i, &t) in types.iter().enumerate() {
+        let (a, b) = counts[t];
+        for w in 0..=half {
+            let skip = best[i][w];
+            let take = if w >= a + b {
+                best[i][w - a - b].map(|c: usize| c + b)
+            } else {
+                None
+            };
+            best[i + 1][w] = match (skip, take) {
+                (Some(s), Some(t)) => Some(s.min(t)),
+                (s, t) => s.or(t),
+            };
+        }
+    }
+    best[types.len()][half]?;
+
+    // walk back to see which types stay in the first compartment
+    let mut keep = [false; 53];
+    let mut w = half;
+    for (i, &t) in types.iter().enumerate().rev() {
+        if best[i + 1][w] != best[i][w] {
+            let (a, b) = counts[t];
+            keep[t] = true;
+            w -= a + b;
+        }
+    }
+
+    let stays = |c: &char| keep[to_priority(c).unwrap() as usize];
+    let outgoing = first.chars().enumerate().filter(|(_, c)| !stays(c));
+    let incoming = second.chars().enumerate().filter(|(_, c)| stays(c));
+    Some(
+        outgoing
+            .zip(incoming)
+            .map(|((l, a), (r, b))| Swap {
+                left: l + 1,
+                right: half + r + 1,
+                items: (a, b),
+            })
+            .collect(),
+    )
+}
+
+impl Display for Swap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}@{}<->{}@{}",
+            self.items.0, self.left, self.items.1, self.right
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::repack::{repack, Swap};
+    use crate::{mask_duplicates, sep_compartments};
+
+    fn apply(sack: &str, swaps: &[Swap]) -> String {
+        let mut items: Vec<char> = sack.chars().collect();
+        for s in swaps {
+            items.swap(s.left - 1, s.right - 1);
+        }
+        items.into_iter().collect()
+    }
+
+    #[test]
+    fn test_repack() {
+        assert_eq!(
+            repack("abab").unwrap(),
+            vec![Swap {
+                left: 2,
+                right: 3,
+                items: ('b', 'a')
+            }]
+        );
+        assert_eq!(repack("abcd").unwrap(), vec![]);
+        // three a's can't fit in a compartment of two
+        assert_eq!(repack("aaab"), None);
+
+        for sack in include_str!("../test.txt").lines() {
+            let swaps = repack(sack).unwrap();
+            let repacked = apply(sack, &swaps);
+            assert_eq!(mask_duplicates(sep_compartments(&repacked)), 0);
+        }
+        // moving the single shared p beats moving anything bigger
+        assert_eq!(repack("vJrwpWtwJgWrhcsFMMfFFhFp").unwrap().len(), 1);
+    }
+}
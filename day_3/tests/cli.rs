@@ -0,0 +1,42 @@
+use std::{env, fs, process::Command};
+
+/// runs the binary on `input` with `args`, giving back (success, stdout, stderr)
+fn run(name: &str, input: &str, args: &[&str]) -> (bool, String, String) {
+    let path = env::temp_dir().join(format!("day_3_{name}_{}.txt", std::process::id()));
+    fs::write(&path, input).unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_day_3"))
+        .arg("-i")
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    (
+        out.status.success(),
+        String::from_utf8(out.stdout).unwrap(),
+        String::from_utf8(out.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_repack_clean_and_several_shared() {
+    // abcd has nothing shared and abab has two shared items, neither would
+    // get past the checks for scoring
+    let (ok, stdout, stderr) = run("repack", "aaab\nabcd\nabab\n", &["-p", "-g", "2"]);
+    assert!(ok, "{stderr}");
+    assert_eq!(
+        stdout,
+        "Line 1: infeasible\nLine 2: nothing to swap\nLine 3: b@2<->a@3\nTotal swaps: 1 (1 sacks infeasible)\n"
+    );
+
+    // but they still need to be made of items
+    let (ok, _, stderr) = run("repack_bad", "ab1b\nabc\n", &["-p"]);
+    assert!(!ok);
+    assert!(stderr.contains("line 1, column 3"));
+    assert!(stderr.contains("line 2: odd number of items"));
+
+    // and without --repack they're rejected as before
+    let (ok, _, stderr) = run("no_repack", "aaab\nabcd\n", &["-g", "2"]);
+    assert!(!ok);
+    assert!(stderr.contains("line 2: no item in both compartments"));
+}
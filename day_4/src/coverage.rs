@@ -0,0 +1,129 @@
+use std::fmt::{self, Display};
+
+use crate::range::SectionRange;
+
+/// how many assignments cover each section, as runs of sections that all
+/// share the same depth
+#[derive(Debug, PartialEq)]
+pub struct Coverage {
+    /// in order from the first covered section to the last, gaps included
+    /// with a depth of 0
+    pub segments: Vec<(SectionRange, usize)>,
+}
+
+/// sweeps across every assignment's start and end, so it's linear in the
+/// number of assignments rather than the number of sections
+///
+/// events are u128 so a range ending at usize::MAX can still end
+pub fn coverage(ranges: &[SectionRange]) -> Coverage {
+    let mut events: Vec<(u128, isize)> = ranges
+        .iter()
+        .flat_map(|r| [(r.start() as u128, 1), (r.end() as u128 + 1, -1)])
+        .collect();
+    events.sort_unstable();
+
+    let mut segments: Vec<(SectionRange, usize)> = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < events.len() {
+        let at = events[i].0;
+        if i > 0 {
+            // the run since the last event ends just before this one, only
+            // the very last event can be past usize::MAX
+            let (from, to) = (events[i - 1].0 as usize, (at - 1) as usize);
+            match segments.last_mut() {
+                Some((run, d)) if *d == depth as usize => {
+                    *run = SectionRange::new(run.start(), to).unwrap();
+                }
+                _ => segments.push((SectionRange::new(from, to).unwrap(), depth as usize)),
+            }
+        }
+        while events.get(i).is_some_and(|e| e.0 == at) {
+            depth += events[i].1;
+            i += 1;
+        }
+    }
+    Coverage { segments }
+}
+
+impl Coverage {
+    pub fn peak(&self) -> usize {
+        self.segments.iter().map(|s| s.1).max().unwrap_or(0)
+    }
+
+    /// the sections covered by the most assignments
+    pub fn deepest(&self) -> Vec<SectionRange> {
+        let peak = self.peak();
+        self.segments
+            .iter()
+            .filter(|s| s.1 == peak && peak > 0)
+            .map(|s| s.0)
+            .collect()
+    }
+
+    /// the thinnest coverage anywhere in `range`
+    pub fn min_depth(&self, range: &SectionRange) -> usize {
+        let first = self.segments.partition_point(|s| s.0.end() < range.start());
+        self.segments[first..]
+            .iter()
+            .take_while(|s| s.0.start() <= range.end())
+            .map(|s| s.1)
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// indexes of the assignments whose every section someone else covers too
+pub fn redundant(ranges: &[SectionRange], coverage: &Coverage) -> Vec<usize> {
+    (0..ranges.len())
+        .filter(|&i| coverage.min_depth(&ranges[i]) >= 2)
+        .collect()
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let deepest: Vec<String> = self.deepest().iter().map(|r| r.to_string()).collect();
+        writeln!(f, "Peak depth: {}", self.peak())?;
+        write!(f, "Most covered: {}", deepest.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::coverage::{coverage, redundant};
+    use crate::range::r;
+
+    #[test]
+    fn test_coverage() {
+        let ranges = [r(2, 4), r(6, 8), r(3, 7), r(3, 3)];
+        let c = coverage(&ranges);
+        assert_eq!(
+            c.segments,
+            vec![
+                (r(2, 2), 1),
+                (r(3, 3), 3),
+                (r(4, 4), 2),
+                (r(5, 5), 1),
+                (r(6, 7), 2),
+                (r(8, 8), 1),
+            ]
+        );
+        assert_eq!(c.peak(), 3);
+        assert_eq!(c.deepest(), vec![r(3, 3)]);
+        assert_eq!(c.min_depth(&r(3, 4)), 2);
+        assert_eq!(c.min_depth(&r(3, 7)), 1);
+        // only the single section is covered elsewhere
+        assert_eq!(redundant(&ranges, &c), vec![3]);
+
+        // gaps show up at depth 0, touching ranges merge
+        let c = coverage(&[r(1, 2), r(3, 4), r(7, 7)]);
+        assert_eq!(c.segments, vec![(r(1, 4), 1), (r(5, 6), 0), (r(7, 7), 1)]);
+        assert_eq!(coverage(&[]).peak(), 0);
+
+        let c = coverage(&[r(1, usize::MAX), r(2, 3)]);
+        assert_eq!(
+            c.segments,
+            vec![(r(1, 1), 1), (r(2, 3), 2), (r(4, usize::MAX), 1)]
+        );
+    }
+}
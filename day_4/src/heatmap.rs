@@ -0,0 +1,152 @@
+use std::fmt::Write;
+use std::iter;
+
+use crate::coverage::Coverage;
+use crate::range::SectionRange;
+
+/// depths past 9 get squashed onto this ramp instead
+const RAMP: &[u8] = b".:-=+*#%@";
+
+/// runs of sections whose depth passes `keep`, with neighbouring runs
+/// joined up, section IDs start at 1 so anything before the first
+/// assignment counts as a gap
+pub fn runs(coverage: &Coverage, keep: impl Fn(usize) -> bool) -> Vec<SectionRange> {
+    let mut segments = coverage.segments.clone();
+    if let Some(first) = segments.first().map(|s| s.0.start()).filter(|&s| s > 1) {
+        segments.insert(0, (SectionRange::new(1, first - 1).unwrap(), 0));
+    }
+
+    let mut out: Vec<SectionRange> = Vec::new();
+    for (r, _) in segments.into_iter().filter(|s| keep(s.1)) {
+        match out.last_mut() {
+            Some(last) if last.end() + 1 == r.start() => {
+                *last = SectionRange::new(last.start(), r.end()).unwrap();
+            }
+            _ => out.push(r),
+        }
+    }
+    out
+}
+
+fn join(ranges: &[SectionRange]) -> String {
+    if ranges.is_empty() {
+        return "none".to_string();
+    }
+    let ranges: Vec<String> = ranges.iter().map(|r| r.to_string()).collect();
+    ranges.join(" ")
+}
+
+pub fn gap_report(coverage: &Coverage) -> String {
+    let last = coverage.segments.last().map_or(0, |s| s.0.end());
+    format!(
+        "Sections: 1-{last}\nUncovered: {}\nCovered once: {}\nOver-covered: {}\n",
+        join(&runs(coverage, |d| d == 0)),
+        join(&runs(coverage, |d| d == 1)),
+        join(&runs(coverage, |d| d > 1)),
+    )
+}
+
+/// one character per section, `width` sections to a row, blank where nobody
+/// is assigned
+///
+/// rows are filled in a segment at a time, and a run of full rows all at
+/// the same depth is shown once, so the map grows with the number of
+/// segments rather than the number of sections
+pub fn heatmap(coverage: &Coverage, width: usize) -> String {
+    let peak = coverage.peak();
+    let cell = |depth: usize| match depth {
+        0 => ' ',
+        d if peak <= 9 => char::from_digit(d as u32, 10).unwrap(),
+        d => RAMP[(d * RAMP.len()).div_ceil(peak) - 1] as char,
+    };
+    let last = coverage.segments.last().map_or(0, |s| s.0.end());
+    let label = last.to_string().len();
+    let mut out = String::new();
+    let mut segments = coverage.segments.iter().peekable();
+    let mut row_start = 1;
+    // ends are checked before stepping past them, the last can be usize::MAX
+    while row_start <= last {
+        let mut row_end = row_start.saturating_add(width - 1).min(last);
+        let mut line = String::new();
+        let mut at = row_start;
+        // where the depth the row ends on runs out
+        let run_end = loop {
+            while segments.peek().is_some_and(|s| s.0.end() < at) {
+                segments.next();
+            }
+            // anything before the first segment is a gap too
+            let (depth, run_end) = match segments.peek() {
+                Some((r, d)) if r.start() <= at => (*d, r.end()),
+                Some((r, _)) => (0, r.start() - 1),
+                None => (0, row_end),
+            };
+            let upto = run_end.min(row_end);
+            line.extend(iter::repeat_n(cell(depth), upto - at + 1));
+            if upto == row_end {
+                break run_end;
+            }
+            at = upto + 1;
+        };
+        writeln!(out, "{row_start:>label$} |{line:<width$}|").unwrap();
+
+        let more = (run_end - row_end) / width;
+        if at == row_start && more > 1 {
+            writeln!(out, "{:>label$}  same again for {more} more rows", "").unwrap();
+            row_end += more * width;
+        }
+        if row_end == last {
+            break;
+        }
+        row_start = row_end + 1;
+    }
+    if peak > 9 {
+        writeln!(
+            out,
+            "{:>label$}  each step along {} is about {} deep",
+            "",
+            String::from_utf8_lossy(RAMP),
+            peak.div_ceil(RAMP.len())
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use crate::coverage::coverage;
+    use crate::heatmap::{gap_report, heatmap, runs};
+    use crate::range::{r, SectionRange};
+
+    #[test]
+    fn test_gap_report() {
+        let c = coverage(&[r(3, 5), r(4, 6), r(9, 9), r(10, 12)]);
+        assert_eq!(runs(&c, |d| d == 0), vec![r(1, 2), r(7, 8)]);
+        assert_eq!(runs(&c, |d| d == 1), vec![r(3, 3), r(6, 6), r(9, 12)]);
+        assert_eq!(
+            gap_report(&c),
+            "Sections: 1-12\nUncovered: 1-2 7-8\nCovered once: 3-3 6-6 9-12\nOver-covered: 4-5\n"
+        );
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let c = coverage(&[r(3, 5), r(4, 6), r(9, 9), r(10, 12)]);
+        assert_eq!(heatmap(&c, 5), " 1 |  122|\n 6 |1  11|\n11 |11   |\n");
+
+        // deep coverage goes onto the ramp
+        let deep: Vec<SectionRange> = (0..18).map(|_| r(1, 1)).chain([r(2, 2)]).collect();
+        let map = heatmap(&coverage(&deep), 4);
+        assert!(map.starts_with("1 |@.  |\n"));
+
+        // full rows at one depth are only shown once
+        let c = coverage(&[r(1, 20), r(22, 22)]);
+        assert_eq!(
+            heatmap(&c, 5),
+            " 1 |11111|\n    same again for 3 more rows\n21 | 1   |\n"
+        );
+        let c = coverage(&[r(1, 4_000_000_000)]);
+        assert_eq!(heatmap(&c, 80).lines().count(), 2);
+        assert_eq!(heatmap(&coverage(&[]), 5), "");
+    }
+}
@@ -1,6 +1,16 @@
-use std::fs;
+use std::{fs, process};
 
-use clap::Parser;
+use clap::{builder::RangedU64ValueParser, Parser};
+use thiserror::Error;
+
+mod coverage;
+mod heatmap;
+mod range;
+mod reassign;
+use coverage::{coverage, redundant};
+use heatmap::{gap_report, heatmap};
+use range::SectionRange;
+use reassign::reassign;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -8,87 +18,267 @@ use clap::Parser;
     version = "v1.0.0",
     about = "Advent of Code (Day 4)"
 )]
-
 struct Cli {
     #[clap(short, long)]
     input: String,
+    /// show what each pair of assignments in a crew shares and what's left
+    /// over
+    #[clap(short, long)]
+    verbose: bool,
+    /// look at every assignment together: the most covered sections and
+    /// which assignments everyone else already covers
+    #[clap(short, long)]
+    overlaps: bool,
+    /// report uncovered and doubled up sections, with a heatmap of them
+    #[clap(short, long)]
+    gaps: bool,
+    /// sections per row of the heatmap
+    #[clap(short, long, default_value_t = 50, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    width: usize,
+    /// suggest the fewest changes to each crew's assignments that stop them
+    /// overlapping without leaving anything uncovered
+    #[clap(short, long)]
+    reassign: bool,
 }
 
-fn to_ranges(s: &str) -> Vec<Vec<usize>> {
-    s.split(',')
-        .map(|x| x.split('-').map(|y| y.parse::<usize>().unwrap()).collect())
-        .collect()
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("invalid range {0:?}, expected START-END")]
+    InvalidRange(String),
+    #[error("range {start}-{end} ends before it starts")]
+    Reversed { start: usize, end: usize },
+    #[error("range 0-{end} starts at section 0, sections are numbered from 1")]
+    ZeroSection { end: usize },
+    #[error("{elves} elves is too many to plan for, the limit is {limit}")]
+    CrewTooLarge { elves: usize, limit: usize },
+    #[error("line {line}: {source}")]
+    OnLine { line: usize, source: Box<Error> },
 }
 
-fn check_range_inclusion(r: &[usize], s: &[usize]) -> bool {
-    (r[0] >= s[0] && r[1] <= s[1]) || (s[0] >= r[0] && s[1] <= r[1])
+fn to_ranges(s: &str) -> Result<Vec<SectionRange>, Error> {
+    s.split(',').map(|x| x.parse()).collect()
 }
 
-fn check_range_overlap(r: &[usize], s: &[usize]) -> bool {
-    if r[0] <= s[0] {
-        r[1] >= s[0]
+/// every line as a crew of however many elves, reporting all the bad lines
+/// at once
+fn parse_crews(s: &str) -> Result<Vec<Vec<SectionRange>>, Vec<Error>> {
+    let mut crews = Vec::new();
+    let mut errors = Vec::new();
+    for (line, l) in s.split_terminator('\n').enumerate() {
+        match to_ranges(l) {
+            Ok(crew) => crews.push(crew),
+            Err(e) => errors.push(Error::OnLine {
+                line: line + 1,
+                source: Box::new(e),
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(crews)
     } else {
-        s[1] >= r[0]
+        Err(errors)
     }
 }
 
-fn solve_part1(s: &str) -> usize {
-    s.split_terminator('\n')
-        .map(to_ranges)
-        .map(|x| {
-            if check_range_inclusion(&x[0], &x[1]) {
-                1
-            } else {
-                0
-            }
-        })
-        .sum()
+/// every (first, second) pairing of elves within a crew
+fn pairs(crew: &[SectionRange]) -> impl Iterator<Item = (&SectionRange, &SectionRange)> {
+    crew.iter()
+        .enumerate()
+        .flat_map(move |(i, r)| crew[i + 1..].iter().map(move |s| (r, s)))
+}
+
+fn solve_part1(crews: &[Vec<SectionRange>]) -> usize {
+    crews
+        .iter()
+        .filter(|c| pairs(c).any(|(r, s)| r.contains(s) || s.contains(r)))
+        .count()
+}
+
+fn solve_part2(crews: &[Vec<SectionRange>]) -> usize {
+    crews
+        .iter()
+        .filter(|c| pairs(c).any(|(r, s)| r.overlaps(s)))
+        .count()
+}
+
+/// ranges as a space separated list, or "none"
+fn join_ranges(ranges: &[SectionRange]) -> String {
+    if ranges.is_empty() {
+        return "none".to_string();
+    }
+    ranges
+        .iter()
+        .map(|r| r.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn describe_pair(r: &SectionRange, s: &SectionRange) -> String {
+    let shared: Vec<SectionRange> = r.intersection(s).into_iter().collect();
+    format!(
+        "{r},{s}: shared {}, only first {}, only second {}, union {}",
+        join_ranges(&shared),
+        join_ranges(&r.difference(s)),
+        join_ranges(&s.difference(r)),
+        join_ranges(&r.union(s))
+    )
+}
+
+fn print_overlaps(crews: &[Vec<SectionRange>]) {
+    let ranges: Vec<SectionRange> = crews.concat();
+    // (line, elf) for each of the ranges
+    let owners: Vec<(usize, usize)> = crews
+        .iter()
+        .enumerate()
+        .flat_map(|(line, c)| (0..c.len()).map(move |elf| (line + 1, elf + 1)))
+        .collect();
+    let c = coverage(&ranges);
+    let redundant = redundant(&ranges, &c);
+    println!("{c}");
+    println!(
+        "Redundant assignments: {} of {}",
+        redundant.len(),
+        ranges.len()
+    );
+    for i in redundant {
+        println!("  Line {}, elf {}: {}", owners[i].0, owners[i].1, ranges[i]);
+    }
 }
 
-fn solve_part2(s: &str) -> usize {
-    s.split_terminator('\n')
-        .map(to_ranges)
-        .map(|x| {
-            if check_range_overlap(&x[0], &x[1]) {
-                1
-            } else {
-                0
+fn print_reassignments(crews: &[Vec<SectionRange>]) {
+    let (mut total, mut stuck) = (0, 0);
+    for (line, crew) in crews.iter().enumerate() {
+        match reassign(crew) {
+            Ok(Some(edits)) if edits.is_empty() => {}
+            Ok(Some(edits)) => {
+                let list: Vec<String> = edits.iter().map(|e| e.to_string()).collect();
+                println!("Line {}: {}", line + 1, list.join(", "));
+                total += edits.len();
+            }
+            Ok(None) => {
+                println!("Line {}: infeasible", line + 1);
+                stuck += 1;
             }
-        })
-        .sum()
+            Err(e) => {
+                println!("Line {}: {e}", line + 1);
+                stuck += 1;
+            }
+        }
+    }
+    println!("Total edits: {total} ({stuck} crews left as they are)");
+}
+
+fn fail(errors: &[Error]) -> ! {
+    for e in errors {
+        eprintln!("{e}");
+    }
+    if errors.len() > 1 {
+        eprintln!("error: found {} problems", errors.len());
+    }
+    process::exit(1);
 }
 
 fn main() {
     let cli_args = Cli::parse();
     let input = &fs::read_to_string(cli_args.input).unwrap();
-    println!("Part 1: {}", solve_part1(input));
-    println!("Part 2: {}", solve_part2(input));
+    let crews = match parse_crews(input) {
+        Ok(crews) => crews,
+        Err(errors) => fail(&errors),
+    };
+    if cli_args.verbose {
+        for (line, crew) in crews.iter().enumerate() {
+            for (r, s) in pairs(crew) {
+                println!("Line {}: {}", line + 1, describe_pair(r, s));
+            }
+        }
+    }
+    if cli_args.overlaps {
+        print_overlaps(&crews);
+    }
+    if cli_args.gaps {
+        let c = coverage(&crews.concat());
+        print!("{}", gap_report(&c));
+        print!("{}", heatmap(&c, cli_args.width));
+    }
+    if cli_args.reassign {
+        print_reassignments(&crews);
+    }
+    println!("Part 1: {}", solve_part1(&crews));
+    println!("Part 2: {}", solve_part2(&crews));
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{check_range_inclusion, check_range_overlap, to_ranges};
+    use crate::range::r;
+    use crate::{describe_pair, pairs, parse_crews, solve_part1, solve_part2, to_ranges, Error};
 
     #[test]
     fn test_to_ranges() {
-        assert_eq!(to_ranges("2-4,6-8"), vec![vec![2, 4], vec![6, 8]]);
-        assert_eq!(to_ranges("2-3,4-5"), vec![vec![2, 3], vec![4, 5]]);
-        assert_eq!(to_ranges("2-8,3-7"), vec![vec![2, 8], vec![3, 7]]);
+        assert_eq!(to_ranges("2-4,6-8"), Ok(vec![r(2, 4), r(6, 8)]));
+        assert_eq!(to_ranges("2-3,4-5"), Ok(vec![r(2, 3), r(4, 5)]));
+        assert_eq!(to_ranges("2-8,3-7"), Ok(vec![r(2, 8), r(3, 7)]));
+        assert_eq!(
+            to_ranges("2-8,7-3"),
+            Err(Error::Reversed { start: 7, end: 3 })
+        );
     }
 
     #[test]
-    fn test_check_range_inclusion() {
-        assert!(check_range_inclusion(&[2, 3], &[2, 4]));
-        assert!(check_range_inclusion(&[6, 6], &[4, 6]));
-        assert!(!check_range_inclusion(&[2, 4], &[6, 8]));
+    fn test_parse_crews() {
+        assert_eq!(
+            parse_crews("2-4,6-8\n2-4\n1-2,3-4,5-6\n").unwrap(),
+            vec![
+                vec![r(2, 4), r(6, 8)],
+                vec![r(2, 4)],
+                vec![r(1, 2), r(3, 4), r(5, 6)]
+            ]
+        );
+        assert_eq!(
+            parse_crews("2-4,6-8\n\n1-2,x\n").unwrap_err(),
+            vec![
+                Error::OnLine {
+                    line: 2,
+                    source: Box::new(Error::InvalidRange("".to_string()))
+                },
+                Error::OnLine {
+                    line: 3,
+                    source: Box::new(Error::InvalidRange("x".to_string()))
+                }
+            ]
+        );
+        // the gap report and heatmap both count from section 1
+        assert_eq!(
+            parse_crews("0-2,1-3\n").unwrap_err(),
+            vec![Error::OnLine {
+                line: 1,
+                source: Box::new(Error::ZeroSection { end: 2 })
+            }]
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let crews = parse_crews(include_str!("../test.txt")).unwrap();
+        assert_eq!(solve_part1(&crews), 2);
+        assert_eq!(solve_part2(&crews), 4);
+
+        // a crew counts once however many of its pairs qualify
+        let crews = parse_crews("1-2,3-4,4-9,5-5\n1-1\n").unwrap();
+        assert_eq!(pairs(&crews[0]).count(), 6);
+        assert_eq!(pairs(&crews[1]).count(), 0);
+        assert_eq!(solve_part1(&crews), 1);
+        assert_eq!(solve_part2(&crews), 1);
     }
 
     #[test]
-    fn test_check_range_overlap() {
-        assert!(check_range_overlap(&[2, 3], &[2, 4]));
-        assert!(check_range_overlap(&[6, 6], &[4, 6]));
-        assert!(!check_range_overlap(&[2, 4], &[6, 8]));
-        assert!(check_range_overlap(&[2, 5], &[4, 6]));
-        assert!(!check_range_overlap(&[7, 8], &[5, 6]));
+    fn test_describe_pair() {
+        assert_eq!(
+            describe_pair(&r(2, 6), &r(4, 8)),
+            "2-6,4-8: shared 4-6, only first 2-3, only second 7-8, union 2-8"
+        );
+        assert_eq!(
+            describe_pair(&r(2, 4), &r(6, 8)),
+            "2-4,6-8: shared none, only first 2-4, only second 6-8, union 2-4 6-8"
+        );
     }
 }
@@ -0,0 +1,179 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::Error;
+
+/// an elf's assignment, start and end sections both included, sections are
+/// numbered from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SectionRange {
+    start: usize,
+    end: usize,
+}
+
+impl SectionRange {
+    pub fn new(start: usize, end: usize) -> Result<Self, Error> {
+        if start > end {
+            return Err(Error::Reversed { start, end });
+        }
+        if start == 0 {
+            return Err(Error::ZeroSection { end });
+        }
+        Ok(SectionRange { start, end })
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// number of sections, never 0, and with no section 0 it always fits
+    pub fn len(&self) -> usize {
+        self.end - self.start + 1
+    }
+
+    /// every section in `other` is in this one too
+    pub fn contains(&self, other: &SectionRange) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &SectionRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &SectionRange) -> Option<SectionRange> {
+        self.overlaps(other).then(|| SectionRange {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        })
+    }
+
+    /// one range if they overlap or sit right next to each other, otherwise
+    /// both of them in order
+    pub fn union(&self, other: &SectionRange) -> Vec<SectionRange> {
+        let (first, second) = if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        if second.start <= first.end.saturating_add(1) {
+            vec![SectionRange {
+                start: first.start,
+                end: first.end.max(second.end),
+            }]
+        } else {
+            vec![*first, *second]
+        }
+    }
+
+    /// the sections in this range but not `other`, either side of it
+    pub fn difference(&self, other: &SectionRange) -> Vec<SectionRange> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+        let mut out = Vec::new();
+        if self.start < other.start {
+            out.push(SectionRange {
+                start: self.start,
+                end: other.start - 1,
+            });
+        }
+        if other.end < self.end {
+            out.push(SectionRange {
+                start: other.end + 1,
+                end: self.end,
+            });
+        }
+        out
+    }
+}
+
+impl FromStr for SectionRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidRange(s.to_string());
+        let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+        let start = start.trim().parse().map_err(|_| invalid())?;
+        let end = end.trim().parse().map_err(|_| invalid())?;
+        SectionRange::new(start, end)
+    }
+}
+
+impl Display for SectionRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// shorthand for building ranges in tests
+#[cfg(test)]
+pub(crate) fn r(start: usize, end: usize) -> SectionRange {
+    SectionRange::new(start, end).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::range::{r, SectionRange};
+    use crate::Error;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(r(3, 3), SectionRange { start: 3, end: 3 });
+        assert_eq!(r(3, 3).len(), 1);
+        assert_eq!(
+            SectionRange::new(4, 2),
+            Err(Error::Reversed { start: 4, end: 2 })
+        );
+        assert_eq!("2-4".parse(), Ok(r(2, 4)));
+        assert_eq!(
+            "4-2".parse::<SectionRange>(),
+            Err(Error::Reversed { start: 4, end: 2 })
+        );
+        assert_eq!(
+            "2-x".parse::<SectionRange>(),
+            Err(Error::InvalidRange("2-x".to_string()))
+        );
+        assert_eq!(r(6, 8).to_string(), "6-8");
+        assert_eq!(
+            "0-2".parse::<SectionRange>(),
+            Err(Error::ZeroSection { end: 2 })
+        );
+        assert_eq!(r(1, usize::MAX).len(), usize::MAX);
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(r(2, 4).contains(&r(2, 3)));
+        assert!(r(4, 6).contains(&r(6, 6)));
+        assert!(!r(2, 3).contains(&r(2, 4)));
+        assert!(!r(6, 8).contains(&r(2, 4)));
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert!(r(2, 3).overlaps(&r(2, 4)));
+        assert!(r(6, 6).overlaps(&r(4, 6)));
+        assert!(!r(2, 4).overlaps(&r(6, 8)));
+        assert!(r(2, 5).overlaps(&r(4, 6)));
+        assert!(!r(7, 8).overlaps(&r(5, 6)));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        assert_eq!(r(2, 5).intersection(&r(4, 8)), Some(r(4, 5)));
+        assert_eq!(r(2, 3).intersection(&r(5, 8)), None);
+
+        assert_eq!(r(4, 8).union(&r(2, 5)), vec![r(2, 8)]);
+        assert_eq!(r(2, 3).union(&r(4, 8)), vec![r(2, 8)]);
+        assert_eq!(r(6, 8).union(&r(2, 4)), vec![r(2, 4), r(6, 8)]);
+
+        assert_eq!(r(2, 8).difference(&r(4, 5)), vec![r(2, 3), r(6, 8)]);
+        assert_eq!(r(2, 5).difference(&r(4, 8)), vec![r(2, 3)]);
+        assert_eq!(r(4, 5).difference(&r(2, 8)), vec![]);
+        assert_eq!(r(2, 3).difference(&r(5, 8)), vec![r(2, 3)]);
+    }
+}
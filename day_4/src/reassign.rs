@@ -0,0 +1,185 @@
+use std::fmt::{self, Display};
+
+use crate::range::SectionRange;
+use crate::Error;
+
+/// most elves in a crew we're willing to try every combination of
+pub const CREW_LIMIT: usize = 16;
+
+/// a new assignment for one elf in a crew, elves count from 0
+#[derive(Debug, PartialEq)]
+pub struct Edit {
+    pub elf: usize,
+    pub from: SectionRange,
+    pub to: SectionRange,
+}
+
+/// the fewest edits that leave no two elves in the crew sharing a section,
+/// while everything the crew covered is still covered, or None when that
+/// can't be done (too few elves to span the gaps, or too many to fit)
+///
+/// tries every set of elves to leave alone, the rest then have to split
+/// up whatever the kept ones don't cover
+pub fn reassign(crew: &[SectionRange]) -> Result<Option<Vec<Edit>>, Error> {
+    let n = crew.len();
+    if n > CREW_LIMIT {
+        return Err(Error::CrewTooLarge {
+            elves: n,
+            limit: CREW_LIMIT,
+        });
+    }
+    let union = union(crew);
+    // clashes[i] has bit j set when elves i and j overlap
+    let clashes: Vec<u32> = crew
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            (0..n)
+                .filter(|&j| j != i && r.overlaps(&crew[j]))
+                .fold(0, |m, j| m | 1 << j)
+        })
+        .collect();
+
+    let mut best: Option<(u32, Vec<SectionRange>)> = None;
+    for keep in 0..1u32 << n {
+        if best
+            .as_ref()
+            .is_some_and(|b| b.0.count_ones() >= keep.count_ones())
+        {
+            continue;
+        }
+        if (0..n).any(|i| keep & 1 << i != 0 && clashes[i] & keep != 0) {
+            continue;
+        }
+        let mut free = union.clone();
+        for r in (0..n).filter(|i| keep & 1 << i != 0).map(|i| crew[i]) {
+            free = free.iter().flat_map(|f| f.difference(&r)).collect();
+        }
+        // every free piece needs an elf, and every elf needs a section
+        let edited = n - keep.count_ones() as usize;
+        let room: usize = free.iter().map(|f| f.len()).sum();
+        if free.len() <= edited && edited <= room {
+            best = Some((keep, free));
+        }
+    }
+
+    let (keep, free) = match best {
+        Some(best) => best,
+        None => return Ok(None),
+    };
+    let mut moved: Vec<usize> = (0..n).filter(|i| keep & 1 << i == 0).collect();
+    moved.sort_by_key(|&i| crew[i]);
+    let targets = split(&free, moved.len());
+    let mut edits: Vec<Edit> = moved
+        .into_iter()
+        .zip(targets)
+        .map(|(elf, to)| Edit {
+            elf,
+            from: crew[elf],
+            to,
+        })
+        .collect();
+    edits.sort_by_key(|e| e.elf);
+    Ok(Some(edits))
+}
+
+/// the crew's sections as separate runs, in order
+fn union(crew: &[SectionRange]) -> Vec<SectionRange> {
+    let mut sorted = crew.to_vec();
+    sorted.sort();
+    let mut out: Vec<SectionRange> = Vec::new();
+    for r in sorted {
+        match out.pop() {
+            Some(last) => out.extend(last.union(&r)),
+            None => out.push(r),
+        }
+    }
+    out
+}
+
+/// cuts the pieces into `count` ranges, one for each piece and the extras
+/// going to whichever pieces still have room, split as evenly as possible
+fn split(pieces: &[SectionRange], count: usize) -> Vec<SectionRange> {
+    let mut shares = vec![1; pieces.len()];
+    let mut extra = count - pieces.len();
+    for (share, piece) in shares.iter_mut().zip(pieces) {
+        let more = extra.min(piece.len() - 1);
+        *share += more;
+        extra -= more;
+    }
+
+    let mut out = Vec::new();
+    for (&share, piece) in shares.iter().zip(pieces) {
+        let mut start = piece.start();
+        for i in 0..share {
+            let len = piece.len() / share + usize::from(i < piece.len() % share);
+            let end = start + (len - 1);
+            out.push(SectionRange::new(start, end).unwrap());
+            // nothing comes after a piece ending at usize::MAX
+            start = end.saturating_add(1);
+        }
+    }
+    out
+}
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "elf {} {} -> {}", self.elf + 1, self.from, self.to)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::range::r;
+    use crate::reassign::{reassign, split, Edit};
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(&[r(2, 8)], 2), vec![r(2, 5), r(6, 8)]);
+        assert_eq!(
+            split(&[r(1, 1), r(3, 5)], 3),
+            vec![r(1, 1), r(3, 4), r(5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_reassign() {
+        // nothing to do when nobody overlaps
+        assert_eq!(reassign(&[r(2, 4), r(6, 8)]).unwrap(), Some(vec![]));
+        assert_eq!(
+            reassign(&[r(5, 7), r(7, 9)]).unwrap(),
+            Some(vec![Edit {
+                elf: 1,
+                from: r(7, 9),
+                to: r(8, 9)
+            }])
+        );
+        // keeping either one leaves two pieces for the other elf
+        assert_eq!(
+            reassign(&[r(2, 8), r(3, 7)]).unwrap(),
+            Some(vec![
+                Edit {
+                    elf: 0,
+                    from: r(2, 8),
+                    to: r(2, 5)
+                },
+                Edit {
+                    elf: 1,
+                    from: r(3, 7),
+                    to: r(6, 8)
+                },
+            ])
+        );
+        // the middle elf bridges the other two, so just one moves
+        let edits = reassign(&[r(1, 3), r(2, 8), r(7, 9)]).unwrap().unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].to, r(4, 6));
+        // three elves can't share two sections
+        assert_eq!(reassign(&[r(1, 2), r(1, 2), r(2, 2)]).unwrap(), None);
+        assert!(reassign(&vec![r(1, 1); 17]).is_err());
+
+        let edits = reassign(&[r(1, usize::MAX), r(2, 3)]).unwrap().unwrap();
+        assert_eq!(edits[0].to, r(1, usize::MAX / 2 + 1));
+        assert_eq!(edits[1].to, r(usize::MAX / 2 + 2, usize::MAX));
+    }
+}
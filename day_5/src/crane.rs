@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use clap::ValueEnum;
+
+use crate::Move;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Model {
+    /// one crate at a time
+    #[value(name = "9000")]
+    CrateMover9000,
+    /// the whole lot in one go
+    #[value(name = "9001")]
+    CrateMover9001,
+    /// up to --lift crates a trip
+    Limited,
+}
+
+impl Model {
+    pub fn build(self, lift: usize) -> Box<dyn Crane> {
+        match self {
+            Model::CrateMover9000 => Box::new(CrateMover9000),
+            Model::CrateMover9001 => Box::new(CrateMover9001),
+            Model::Limited => Box::new(Limited { lift }),
+        }
+    }
+}
+
+/// something that carries out a move, the move's already been checked
+pub trait Crane {
+    fn name(&self) -> String;
+
+    fn operate(&self, stacks: &mut [VecDeque<char>], op: &Move);
+}
+
+/// takes `count` crates off the top of `from` in one trip, keeping their
+/// order, and puts them on `to`
+fn trip(stacks: &mut [VecDeque<char>], from: usize, to: usize, count: usize) {
+    let elfboxes: Vec<char> = stacks[from - 1].drain(..count).collect();
+    for elfbox in elfboxes.into_iter().rev() {
+        stacks[to - 1].push_front(elfbox);
+    }
+}
+
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        "CrateMover 9000".to_string()
+    }
+
+    fn operate(&self, stacks: &mut [VecDeque<char>], op: &Move) {
+        for _ in 0..op.count {
+            trip(stacks, op.from, op.to, 1);
+        }
+    }
+}
+
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        "CrateMover 9001".to_string()
+    }
+
+    fn operate(&self, stacks: &mut [VecDeque<char>], op: &Move) {
+        trip(stacks, op.from, op.to, op.count);
+    }
+}
+
+/// lifts at most `lift` crates at a time, so a 1 behaves like the 9000 and
+/// anything at least as big as the move like the 9001
+pub struct Limited {
+    pub lift: usize,
+}
+
+impl Crane for Limited {
+    fn name(&self) -> String {
+        format!("Limited crane ({} per trip)", self.lift)
+    }
+
+    fn operate(&self, stacks: &mut [VecDeque<char>], op: &Move) {
+        let mut left = op.count;
+        while left > 0 {
+            let n = left.min(self.lift);
+            trip(stacks, op.from, op.to, n);
+            left -= n;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use crate::crane::{Crane, CrateMover9000, CrateMover9001, Limited};
+    use crate::Move;
+
+    fn lift(crane: &dyn Crane) -> Vec<VecDeque<char>> {
+        let mut stacks = vec![
+            VecDeque::from(vec!['A', 'B', 'C', 'D', 'E']),
+            VecDeque::new(),
+        ];
+        crane.operate(
+            &mut stacks,
+            &Move {
+                count: 5,
+                from: 1,
+                to: 2,
+            },
+        );
+        stacks
+    }
+
+    #[test]
+    fn test_cranes() {
+        assert_eq!(lift(&CrateMover9000)[1], vec!['E', 'D', 'C', 'B', 'A']);
+        assert_eq!(lift(&CrateMover9001)[1], vec!['A', 'B', 'C', 'D', 'E']);
+        // A B, then C D, then E on top
+        assert_eq!(lift(&Limited { lift: 2 })[1], vec!['E', 'C', 'D', 'A', 'B']);
+        assert_eq!(lift(&Limited { lift: 1 }), lift(&CrateMover9000));
+        assert_eq!(lift(&Limited { lift: 9 }), lift(&CrateMover9001));
+        assert!(lift(&CrateMover9001)[0].is_empty());
+    }
+}
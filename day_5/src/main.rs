@@ -1,5 +1,9 @@
-use clap::Parser;
-use std::{collections::VecDeque, fs};
+use clap::{builder::RangedU64ValueParser, Parser};
+use std::{collections::VecDeque, fs, ops::Range, process};
+use thiserror::Error;
+
+mod crane;
+use crane::{Crane, CrateMover9000, CrateMover9001, Model};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -7,141 +11,396 @@ use std::{collections::VecDeque, fs};
     version = "v1.0.0",
     about = "Advent of Code (Day 5)"
 )]
-
 struct Cli {
     #[clap(short, long)]
     input: String,
+    /// just run this crane instead of the two from the puzzle
+    #[clap(short, long, value_enum)]
+    crane: Option<Model>,
+    /// most crates the limited crane can carry at once
+    #[clap(short, long, default_value_t = 3, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    lift: usize,
 }
 
-fn split_input(s: &str) -> Vec<&str> {
-    s.split("\n\n").collect()
+#[derive(Error, Debug, PartialEq)]
+pub enum Error {
+    #[error("expected a blank line between the drawing and the moves")]
+    MissingMoves,
+    #[error("line {line}: no stack labels under the drawing")]
+    NoLabels { line: usize },
+    #[error("line {line}, column {column}: {label:?} isn't a stack number")]
+    InvalidLabel {
+        line: usize,
+        column: usize,
+        label: String,
+    },
+    #[error("line {line}, column {column}: expected stack {expected}, found {found}")]
+    LabelOrder {
+        line: usize,
+        column: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("line {line}, column {column}: expected a crate like [A]")]
+    InvalidCrate { line: usize, column: usize },
+    #[error("line {line}, column {column}: crate isn't above any stack label")]
+    Misaligned { line: usize, column: usize },
+    #[error("line {line}: expected \"move N from A to B\", found {text:?}")]
+    InvalidMove { line: usize, text: String },
+    #[error("line {line}: there's no stack {stack}, stacks are {state}")]
+    NoSuchStack {
+        line: usize,
+        stack: usize,
+        state: String,
+    },
+    #[error("line {line}: can't take {count} crates off stack {from}, stacks are {state}")]
+    NotEnoughCrates {
+        line: usize,
+        count: usize,
+        from: usize,
+        state: String,
+    },
 }
 
-fn parse_stacks(s: &str) -> Vec<VecDeque<char>> {
-    let mut out: Vec<VecDeque<char>> = Vec::new();
+/// each stack's crates, top first
+type Stacks = Vec<VecDeque<char>>;
 
-    // pre-allocate our stacks
-    let num_stacks = 9;
-    for _ in 0..num_stacks {
-        out.push(VecDeque::new());
-    }
+/// `count` crates from stack `from` to stack `to`, stacks count from 1
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Move {
+    count: usize,
+    from: usize,
+    to: usize,
+}
 
-    // parse each row and send elements to their
-    // corresponding stack
-    for row in s.split('\n') {
-        let mut row = String::from(row);
-        row.push(' '); // our rows are 1 short for group_items to work, pad it out
-        let columns = group_items(row.chars().collect::<Vec<char>>(), 4);
-        for c in columns.into_iter().enumerate() {
-            if let Some(elfbox) = to_elfbox(&c.1) {
-                let stack_num = c.0 % num_stacks;
-                out[stack_num].push_back(elfbox);
+impl Move {
+    /// makes sure the move can be done, so applying it can't panic
+    fn check(&self, line: usize, stacks: &[VecDeque<char>]) -> Result<(), Error> {
+        for stack in [self.from, self.to] {
+            if stack == 0 || stack > stacks.len() {
+                return Err(Error::NoSuchStack {
+                    line,
+                    stack,
+                    state: describe_stacks(stacks),
+                });
             }
         }
+        if stacks[self.from - 1].len() < self.count {
+            return Err(Error::NotEnoughCrates {
+                line,
+                count: self.count,
+                from: self.from,
+                state: describe_stacks(stacks),
+            });
+        }
+        Ok(())
     }
-    out
 }
 
-fn to_elfbox(s: &str) -> Option<char> {
-    if s.trim_start().is_empty() {
-        return None;
-    }
-    let res = s.trim_start_matches('[').chars().next().unwrap();
-    if res.is_alphabetic() {
-        Some(res)
-    } else {
-        None
-    }
+/// like "1: ZN, 2: MCD, 3: -", each stack bottom to top
+fn describe_stacks(stacks: &[VecDeque<char>]) -> String {
+    stacks
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let crates: String = s.iter().rev().collect();
+            if crates.is_empty() {
+                format!("{}: -", i + 1)
+            } else {
+                format!("{}: {crates}", i + 1)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
-/// panics if items is not evenly divisble by group_len
-fn group_items(items: Vec<char>, group_len: usize) -> Vec<String> {
-    let mut inter = Vec::new();
+/// the drawing and the moves, split on the blank line between them
+fn split_input(s: &str) -> Result<(&str, &str), Error> {
+    s.split_once("\n\n").ok_or(Error::MissingMoves)
+}
 
-    let mut g = 0;
-    let groups = items.len() / group_len;
-    while g < groups {
-        inter.push(vec![]);
-        g += 1;
-    }
+/// each stack from the top down, the stack count and where each stack's
+/// crates sit come from the numbered row at the bottom, rows above it can
+/// stop short after their last crate
+fn parse_stacks(s: &str) -> Result<Stacks, Error> {
+    let rows: Vec<&str> = s.trim_end_matches('\n').split('\n').collect();
+    let label_line = rows.len();
+    let labels = parse_labels(rows[label_line - 1], label_line)?;
 
-    for item in items.into_iter().enumerate() {
-        let group_num = item.0 / group_len;
-        inter[group_num].push(item.1);
+    let mut out = vec![VecDeque::new(); labels.len()];
+    for (line, row) in rows[..label_line - 1].iter().enumerate() {
+        let items: Vec<char> = row.chars().collect();
+        let mut c = 0;
+        while c < items.len() {
+            if items[c] == ' ' {
+                c += 1;
+                continue;
+            }
+            let elfbox = match items.get(c..c + 3) {
+                Some(&['[', b, ']']) if b.is_alphabetic() => b,
+                _ => {
+                    return Err(Error::InvalidCrate {
+                        line: line + 1,
+                        column: c + 1,
+                    })
+                }
+            };
+            // the crate's letter has to sit over its stack's number
+            let stack =
+                labels
+                    .iter()
+                    .position(|l| l.contains(&(c + 1)))
+                    .ok_or(Error::Misaligned {
+                        line: line + 1,
+                        column: c + 2,
+                    })?;
+            out[stack].push_back(elfbox);
+            c += 3;
+        }
     }
+    Ok(out)
+}
 
+/// the columns each label covers, labels have to count up from 1
+fn parse_labels(row: &str, line: usize) -> Result<Vec<Range<usize>>, Error> {
     let mut out = Vec::new();
-    for i in inter {
-        out.push(i.into_iter().collect::<String>());
+    let items: Vec<char> = row.chars().collect();
+    let mut c = 0;
+    while c < items.len() {
+        if items[c] == ' ' {
+            c += 1;
+            continue;
+        }
+        let start = c;
+        while c < items.len() && items[c] != ' ' {
+            c += 1;
+        }
+        let label: String = items[start..c].iter().collect();
+        let found = label.parse::<usize>().map_err(|_| Error::InvalidLabel {
+            line,
+            column: start + 1,
+            label: label.clone(),
+        })?;
+        if found != out.len() + 1 {
+            return Err(Error::LabelOrder {
+                line,
+                column: start + 1,
+                expected: out.len() + 1,
+                found,
+            });
+        }
+        out.push(start..c);
+    }
+    if out.is_empty() {
+        return Err(Error::NoLabels { line });
     }
-    out
+    Ok(out)
 }
 
-fn parse_operations(s: &str) -> Vec<Vec<usize>> {
+/// every move along with its line in the file, `first_line` being the line
+/// the moves start on
+fn parse_operations(s: &str, first_line: usize) -> Result<Vec<(usize, Move)>, Error> {
     let mut out = Vec::new();
-    for op_string in s.split('\n') {
-        let mut op = Vec::new();
-        for o in op_string
-            .replace(|x: char| x.is_alphabetic(), "")
-            .split(' ')
-        {
-            if !o.is_empty() {
-                op.push(o.parse::<usize>().unwrap());
-            }
-        }
-        out.push(op);
+    for (i, op_string) in s.split_terminator('\n').enumerate() {
+        let line = first_line + i;
+        let invalid = || Error::InvalidMove {
+            line,
+            text: op_string.to_string(),
+        };
+        let words: Vec<&str> = op_string.split_whitespace().collect();
+        let op = match words[..] {
+            ["move", count, "from", from, "to", to] => Move {
+                count: count.parse().map_err(|_| invalid())?,
+                from: from.parse().map_err(|_| invalid())?,
+                to: to.parse().map_err(|_| invalid())?,
+            },
+            _ => return Err(invalid()),
+        };
+        out.push((line, op));
     }
-    out
+    Ok(out)
 }
 
-fn do_operation(stacks: &mut [VecDeque<char>], op: &[usize]) {
-    for _ in 0..op[0] {
-        let elfbox = stacks[op[1] - 1].pop_front().unwrap();
-        stacks[op[2] - 1].push_front(elfbox);
+/// checks each move against the stacks as they are at that point before
+/// doing it
+fn run(
+    stacks: &mut [VecDeque<char>],
+    operations: &[(usize, Move)],
+    crane: &dyn Crane,
+) -> Result<(), Error> {
+    for (line, op) in operations {
+        op.check(*line, stacks)?;
+        crane.operate(stacks, op);
     }
+    Ok(())
 }
 
-fn do_operation_p2(stacks: &mut [VecDeque<char>], op: &[usize]) {
-    let mut elfboxes = VecDeque::new();
-    for _ in 0..op[0] {
-        elfboxes.push_back(stacks[op[1] - 1].pop_front().unwrap());
-    }
-    for _ in 0..op[0] {
-        stacks[op[2] - 1].push_front(elfboxes.pop_back().unwrap());
-    }
+/// the starting stacks and the moves to make on them
+fn parse_input(s: &str) -> Result<(Stacks, Vec<(usize, Move)>), Error> {
+    let (drawing, moves) = split_input(s)?;
+    let stacks = parse_stacks(drawing)?;
+    // the moves start after the drawing and the blank line
+    let first_line = drawing.split('\n').count() + 2;
+    Ok((stacks, parse_operations(moves, first_line)?))
 }
 
 fn main() {
     let cli_args = Cli::parse();
     let input = &fs::read_to_string(cli_args.input).unwrap();
-    println!("Part 1: {}", solve_part1(input));
-    println!("Part 2: {}", solve_part2(input));
+    let answer = |crane: &dyn Crane| match solve(input, crane) {
+        Ok(tops) => tops,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+
+    match cli_args.crane {
+        Some(model) => {
+            let crane = model.build(cli_args.lift);
+            println!("{}: {}", crane.name(), answer(crane.as_ref()));
+        }
+        None => {
+            println!("Part 1: {}", answer(&CrateMover9000));
+            println!("Part 2: {}", answer(&CrateMover9001));
+        }
+    }
+}
+
+/// the crate on top of each stack, empty stacks are skipped
+fn tops(stacks: &[VecDeque<char>]) -> String {
+    stacks.iter().filter_map(|s| s.front()).collect()
 }
 
-fn solve_part1(s: &str) -> String {
-    let inputs = split_input(s);
-    let mut output = String::new();
-    let mut stacks = parse_stacks(inputs[0]);
-    let operations = parse_operations(inputs[1]);
-    for op in operations {
-        do_operation(&mut stacks, &op);
+fn solve(s: &str, crane: &dyn Crane) -> Result<String, Error> {
+    let (mut stacks, operations) = parse_input(s)?;
+    run(&mut stacks, &operations, crane)?;
+    Ok(tops(&stacks))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::crane::{CrateMover9000, CrateMover9001, Limited};
+    use crate::{parse_operations, parse_stacks, solve, Error, Move};
+
+    #[test]
+    fn test_parse_stacks() {
+        let stacks = parse_stacks("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3").unwrap();
+        assert_eq!(stacks, vec![vec!['N', 'Z'], vec!['D', 'C', 'M'], vec!['P']]);
+
+        // two stacks, and ten
+        assert_eq!(parse_stacks("[A]\n 1   2 ").unwrap().len(), 2);
+        let wide = "[A]                                [B]\n 1   2   3   4   5   6   7   8   9  10";
+        let stacks = parse_stacks(wide).unwrap();
+        assert_eq!(stacks.len(), 10);
+        assert_eq!(stacks[9], vec!['B']);
     }
-    for stack in stacks {
-        output.push(stack[0]);
+
+    #[test]
+    fn test_parse_stacks_errors() {
+        assert_eq!(
+            parse_stacks("[A]\n 1   3"),
+            Err(Error::LabelOrder {
+                line: 2,
+                column: 6,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            parse_stacks("[A]\n 1   x"),
+            Err(Error::InvalidLabel {
+                line: 2,
+                column: 6,
+                label: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_stacks("[A]\n"),
+            Err(Error::InvalidLabel {
+                line: 1,
+                column: 1,
+                label: "[A]".to_string()
+            })
+        );
+        assert_eq!(
+            parse_stacks("  [A]\n 1   2"),
+            Err(Error::Misaligned { line: 1, column: 4 })
+        );
+        assert_eq!(
+            parse_stacks("[A] [B\n 1   2"),
+            Err(Error::InvalidCrate { line: 1, column: 5 })
+        );
+        assert_eq!(parse_stacks("   \n"), Err(Error::NoLabels { line: 1 }));
+    }
+
+    #[test]
+    fn test_solve() {
+        let input = include_str!("../test.txt");
+        assert_eq!(solve(input, &CrateMover9000).unwrap(), "CMZ");
+        assert_eq!(solve(input, &CrateMover9001).unwrap(), "MCD");
+        // the move of 3 takes two trips, leaving Z on top
+        assert_eq!(solve(input, &Limited { lift: 2 }).unwrap(), "MCZ");
+        assert_eq!(solve("[A]\n 1 ", &CrateMover9000), Err(Error::MissingMoves));
     }
-    output
-}
 
-fn solve_part2(s: &str) -> String {
-    let inputs = split_input(s);
-    let mut output = String::new();
-    let mut stacks = parse_stacks(inputs[0]);
-    let operations = parse_operations(inputs[1]);
-    for op in operations {
-        do_operation_p2(&mut stacks, &op);
+    #[test]
+    fn test_parse_operations() {
+        assert_eq!(
+            parse_operations("move 1 from 2 to 1\nmove 10 from 1 to 3\n", 6),
+            Ok(vec![
+                (
+                    6,
+                    Move {
+                        count: 1,
+                        from: 2,
+                        to: 1
+                    }
+                ),
+                (
+                    7,
+                    Move {
+                        count: 10,
+                        from: 1,
+                        to: 3
+                    }
+                ),
+            ])
+        );
+        assert_eq!(
+            parse_operations("move 1 from 2 to 1\nmove 3 from 1 to\n", 6),
+            Err(Error::InvalidMove {
+                line: 7,
+                text: "move 3 from 1 to".to_string()
+            })
+        );
+        assert!(parse_operations("step 1 from 2 to 1", 1).is_err());
+        assert!(parse_operations("move -1 from 2 to 1", 1).is_err());
     }
-    for stack in stacks {
-        output.push(stack[0]);
+
+    #[test]
+    fn test_checked_moves() {
+        let drawing = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3 \n\n";
+        assert_eq!(
+            solve(
+                &format!("{drawing}move 1 from 2 to 1\nmove 4 from 1 to 3\n"),
+                &CrateMover9000
+            ),
+            Err(Error::NotEnoughCrates {
+                line: 7,
+                count: 4,
+                from: 1,
+                state: "1: ZND, 2: MC, 3: P".to_string()
+            })
+        );
+        assert_eq!(
+            solve(&format!("{drawing}move 1 from 4 to 1\n"), &CrateMover9001),
+            Err(Error::NoSuchStack {
+                line: 6,
+                stack: 4,
+                state: "1: ZN, 2: MCD, 3: P".to_string()
+            })
+        );
+        assert!(solve(&format!("{drawing}move 1 from 0 to 1\n"), &CrateMover9000).is_err());
     }
-    output
 }